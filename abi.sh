@@ -0,0 +1,5 @@
+#!/bin/bash
+set -e
+
+cargo near abi --manifest-path contract/Cargo.toml --doc --out-dir res
+node widgets/abi-to-ts.js res/near_contribute_abi.json widgets/contract.d.ts
@@ -0,0 +1,645 @@
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::U128;
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, near_bindgen, require, AccountId, Balance, StorageUsage};
+use schemars::JsonSchema;
+use std::collections::BTreeMap;
+
+use crate::contribution::{Contribution, ContributionNeed};
+use crate::entity::Capability;
+use crate::errors::ContractError;
+use crate::events::Events;
+use crate::pause::PauseCategory;
+use crate::storage::StorageRecord;
+use crate::token::{Payment, Token};
+use crate::{Contract, ContractExt};
+
+/// What funds in escrow are held for.
+#[derive(
+    BorshSerialize,
+    BorshDeserialize,
+    Deserialize,
+    Serialize,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Clone,
+    JsonSchema,
+)]
+#[serde(crate = "near_sdk::serde")]
+pub enum Escrow {
+    /// A need of the entity, paid to the contributor the entity finishes a contribution for it
+    /// with.
+    Need { entity_id: AccountId, cid: String },
+    /// A contribution, paid to its contributor when the entity finishes it.
+    Contribution {
+        entity_id: AccountId,
+        contributor_id: AccountId,
+        contribution_id: u32,
+    },
+}
+
+impl Escrow {
+    /// The entity that pays out the escrow.
+    pub fn entity_id(&self) -> &AccountId {
+        match self {
+            Escrow::Need { entity_id, .. } | Escrow::Contribution { entity_id, .. } => entity_id,
+        }
+    }
+}
+
+/// Funds held in escrow until they are paid out or refunded.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Default)]
+pub struct Bounty {
+    /// Amount deposited by each funder, per token.
+    pub deposits: BTreeMap<(Token, AccountId), Balance>,
+}
+
+impl Bounty {
+    /// Total amount held per token.
+    pub fn totals(&self) -> BTreeMap<Token, Balance> {
+        let mut totals = BTreeMap::new();
+        for ((token, _), amount) in &self.deposits {
+            *totals.entry(token.clone()).or_default() += amount;
+        }
+        totals
+    }
+}
+
+/// Funds held for a need, as stored when bounties could only be paid in NEAR.
+#[derive(BorshSerialize, BorshDeserialize, Clone)]
+pub struct BountyV1 {
+    pub deposits: BTreeMap<AccountId, Balance>,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Clone)]
+pub enum VersionedBounty {
+    V1(BountyV1),
+    Current(Bounty),
+}
+
+impl From<VersionedBounty> for Bounty {
+    fn from(value: VersionedBounty) -> Self {
+        match value {
+            VersionedBounty::V1(b) => Bounty {
+                deposits: b
+                    .deposits
+                    .into_iter()
+                    .map(|(account_id, amount)| ((Token::Near, account_id), amount))
+                    .collect(),
+            },
+            VersionedBounty::Current(b) => b,
+        }
+    }
+}
+
+impl Contract {
+    fn get_bounty(&self, escrow: &Escrow) -> Option<Bounty> {
+        match escrow {
+            Escrow::Need { entity_id, cid } => self.bounties.get(&(entity_id.clone(), cid.clone())),
+            Escrow::Contribution {
+                entity_id,
+                contributor_id,
+                contribution_id,
+            } => self.contribution_bounties.get(&(
+                entity_id.clone(),
+                contributor_id.clone(),
+                *contribution_id,
+            )),
+        }
+        .map(|bounty| bounty.clone().into())
+    }
+
+    fn set_bounty(&mut self, escrow: &Escrow, bounty: Bounty) {
+        let bounty = VersionedBounty::Current(bounty);
+        match escrow.clone() {
+            Escrow::Need { entity_id, cid } => {
+                self.bounties.insert((entity_id, cid), bounty);
+            }
+            Escrow::Contribution {
+                entity_id,
+                contributor_id,
+                contribution_id,
+            } => {
+                self.contribution_bounties
+                    .insert((entity_id, contributor_id, contribution_id), bounty);
+            }
+        }
+    }
+
+    /// Removes the escrow, refunding its storage to the funders.
+    fn take_bounty(&mut self, escrow: &Escrow, storage_usage: &mut StorageUsage) -> Option<Bounty> {
+        self.release_record(
+            &StorageRecord::Escrow(escrow.clone()),
+            storage_usage,
+            |this| {
+                match escrow.clone() {
+                    Escrow::Need { entity_id, cid } => this.bounties.remove(&(entity_id, cid)),
+                    Escrow::Contribution {
+                        entity_id,
+                        contributor_id,
+                        contribution_id,
+                    } => this.contribution_bounties.remove(&(
+                        entity_id,
+                        contributor_id,
+                        contribution_id,
+                    )),
+                }
+                .map(Into::into)
+            },
+        )
+    }
+
+    /// Checks that the account can fund the escrow: entity managers (or higher) fund active needs
+    /// and ongoing contributions.
+    pub(crate) fn assert_can_fund(&self, escrow: &Escrow, account_id: &AccountId) {
+        match escrow {
+            Escrow::Need { entity_id, cid } => {
+                self.assert_capability(entity_id, account_id, Capability::PostNeeds);
+                let need = ContributionNeed::from(
+                    self.needs
+                        .get(&(entity_id.clone(), cid.clone()))
+                        .unwrap_or_else(|| ContractError::NoContributionNeed.panic())
+                        .clone(),
+                );
+                require!(need.active, ContractError::NeedNotActive.to_string());
+            }
+            Escrow::Contribution {
+                entity_id,
+                contributor_id,
+                contribution_id,
+            } => {
+                self.assert_capability(entity_id, account_id, Capability::ApproveRequests);
+                let contribution = Contribution::from(
+                    self.contributions
+                        .get(&(entity_id.clone(), contributor_id.clone()))
+                        .unwrap_or_else(|| ContractError::NoContribution.panic())
+                        .clone(),
+                );
+                let detail = contribution
+                    .details
+                    .get(*contribution_id as usize)
+                    .unwrap_or_else(|| ContractError::NoContribution.panic());
+                require!(
+                    detail.end_date.is_none(),
+                    ContractError::ContributionFinished.to_string()
+                );
+            }
+        }
+    }
+
+    /// Whether the escrow can still be paid out: its entity exists and isn't closed or being
+    /// removed, and its need is active or its contribution ongoing.
+    pub(crate) fn is_escrow_open(&self, escrow: &Escrow) -> bool {
+        if !self.is_entity_open(escrow.entity_id()) {
+            return false;
+        }
+        match escrow {
+            Escrow::Need { entity_id, cid } => self
+                .needs
+                .get(&(entity_id.clone(), cid.clone()))
+                .map(|need| ContributionNeed::from(need.clone()).active)
+                .unwrap_or_default(),
+            Escrow::Contribution {
+                entity_id,
+                contributor_id,
+                contribution_id,
+            } => self
+                .contributions
+                .get(&(entity_id.clone(), contributor_id.clone()))
+                .and_then(|contribution| {
+                    Contribution::from(contribution.clone())
+                        .details
+                        .get(*contribution_id as usize)
+                        .map(|detail| detail.end_date.is_none())
+                })
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Adds the amount of the token deposited by the account to the escrow. The caller charges
+    /// the account for the escrow record, the token totals are kept by the contract.
+    pub(crate) fn add_to_bounty(
+        &mut self,
+        escrow: &Escrow,
+        account_id: &AccountId,
+        token: Token,
+        amount: Balance,
+        storage_usage: &mut StorageUsage,
+    ) -> Events {
+        self.absorb_storage(storage_usage, |this| {
+            *this.escrowed.entry(token.clone()).or_default() += amount;
+        });
+        let mut bounty = self.get_bounty(escrow).unwrap_or_default();
+        *bounty
+            .deposits
+            .entry((token.clone(), account_id.clone()))
+            .or_default() += amount;
+        self.set_bounty(escrow, bounty);
+        Events::FundEscrow {
+            escrow: escrow.clone(),
+            account_id: account_id.clone(),
+            token,
+            amount,
+        }
+    }
+
+    /// Pays the whole escrow, if any, to the contributor, one transfer per token.
+    pub(crate) fn release_bounty(
+        &mut self,
+        escrow: &Escrow,
+        contributor_id: &AccountId,
+        contribution_id: u32,
+        storage_usage: &mut StorageUsage,
+    ) -> Vec<Events> {
+        let Some(bounty) = self.take_bounty(escrow, storage_usage) else {
+            return vec![];
+        };
+        let mut deposits: BTreeMap<Token, Vec<(AccountId, Balance)>> = BTreeMap::new();
+        for ((token, account_id), amount) in bounty.deposits {
+            deposits
+                .entry(token)
+                .or_default()
+                .push((account_id, amount));
+        }
+        deposits
+            .into_iter()
+            .map(|(token, deposits)| {
+                let amount = deposits.iter().map(|(_, amount)| amount).sum();
+                self.transfer_escrowed(
+                    escrow.clone(),
+                    token.clone(),
+                    contributor_id.clone(),
+                    deposits,
+                    Some(contribution_id),
+                );
+                Events::ReleaseEscrow {
+                    escrow: escrow.clone(),
+                    contributor_id: contributor_id.clone(),
+                    contribution_id,
+                    token,
+                    amount,
+                }
+            })
+            .collect()
+    }
+
+    /// Takes up to the amount of the token out of the escrow, from the deposits in funder order.
+    /// Returns the amount taken from each funder.
+    fn draw_from_bounty(
+        &mut self,
+        escrow: &Escrow,
+        token: &Token,
+        amount: Balance,
+        storage_usage: &mut StorageUsage,
+    ) -> Vec<(AccountId, Balance)> {
+        let Some(mut bounty) = self.get_bounty(escrow) else {
+            return vec![];
+        };
+        let mut remaining = amount;
+        let mut deposits = vec![];
+        for ((deposit_token, account_id), amount) in bounty.deposits.iter_mut() {
+            if deposit_token != token || remaining == 0 {
+                continue;
+            }
+            let taken = remaining.min(*amount);
+            *amount -= taken;
+            remaining -= taken;
+            deposits.push((account_id.clone(), taken));
+        }
+        bounty.deposits.retain(|_, amount| *amount > 0);
+        if bounty.deposits.is_empty() {
+            self.take_bounty(escrow, storage_usage);
+        } else {
+            self.set_bounty(escrow, bounty);
+            self.charge_record(
+                &StorageRecord::Escrow(escrow.clone()),
+                &env::predecessor_account_id(),
+                storage_usage,
+            );
+        }
+        deposits
+    }
+
+    /// Pays part of the escrow to the contributor, drawn from the deposits of the payment's token
+    /// in funder order.
+    pub(crate) fn release_from_bounty(
+        &mut self,
+        escrow: &Escrow,
+        payment: Payment,
+        contributor_id: &AccountId,
+        contribution_id: u32,
+        storage_usage: &mut StorageUsage,
+    ) -> Events {
+        let deposits = self.draw_from_bounty(escrow, &payment.token, payment.amount, storage_usage);
+        require!(
+            deposits.iter().map(|(_, amount)| amount).sum::<Balance>() == payment.amount,
+            ContractError::InsufficientEscrow.to_string()
+        );
+        self.transfer_escrowed(
+            escrow.clone(),
+            payment.token.clone(),
+            contributor_id.clone(),
+            deposits,
+            Some(contribution_id),
+        );
+        Events::ReleaseEscrow {
+            escrow: escrow.clone(),
+            contributor_id: contributor_id.clone(),
+            contribution_id,
+            token: payment.token,
+            amount: payment.amount,
+        }
+    }
+
+    /// Returns up to the payment's amount of the escrow to the funders it is drawn from.
+    pub(crate) fn refund_from_bounty(
+        &mut self,
+        escrow: &Escrow,
+        payment: Payment,
+        storage_usage: &mut StorageUsage,
+    ) -> Vec<Events> {
+        self.draw_from_bounty(escrow, &payment.token, payment.amount, storage_usage)
+            .into_iter()
+            .map(|(account_id, amount)| {
+                self.transfer_escrowed(
+                    escrow.clone(),
+                    payment.token.clone(),
+                    account_id.clone(),
+                    vec![(account_id.clone(), amount)],
+                    None,
+                );
+                Events::RefundEscrow {
+                    escrow: escrow.clone(),
+                    account_id,
+                    token: payment.token.clone(),
+                    amount,
+                }
+            })
+            .collect()
+    }
+
+    /// Returns the deposits of the escrow, if any, to their funders.
+    pub(crate) fn refund_bounty(
+        &mut self,
+        escrow: &Escrow,
+        storage_usage: &mut StorageUsage,
+    ) -> Vec<Events> {
+        let Some(bounty) = self.take_bounty(escrow, storage_usage) else {
+            return vec![];
+        };
+        bounty
+            .deposits
+            .into_iter()
+            .map(|((token, account_id), amount)| {
+                self.transfer_escrowed(
+                    escrow.clone(),
+                    token.clone(),
+                    account_id.clone(),
+                    vec![(account_id.clone(), amount)],
+                    None,
+                );
+                Events::RefundEscrow {
+                    escrow: escrow.clone(),
+                    account_id,
+                    token,
+                    amount,
+                }
+            })
+            .collect()
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Attach the deposit to the escrow of an active contribution need or an ongoing
+    /// contribution. Need bounties are paid to the contributor when an entity manager finishes a
+    /// contribution for the need, and refunded to the funders if the need is deactivated or the
+    /// entity removed. Contribution escrows are paid when an entity manager finishes the
+    /// contribution, and refunded if the entity cancels them or is removed. Fungible tokens are
+    /// escrowed through `ft_transfer_call` instead.
+    #[payable]
+    pub fn fund_escrow(&mut self, escrow: Escrow) {
+        self.assert_not_paused(Some(PauseCategory::Needs));
+        let mut storage_usage = env::storage_usage();
+        let account_id = env::predecessor_account_id();
+        let amount = env::attached_deposit();
+        require!(amount > 0, ContractError::ZeroDeposit.to_string());
+        self.assert_can_fund(&escrow, &account_id);
+        let event = self.add_to_bounty(
+            &escrow,
+            &account_id,
+            Token::Near,
+            amount,
+            &mut storage_usage,
+        );
+        self.charge_record(
+            &StorageRecord::Escrow(escrow),
+            &account_id,
+            &mut storage_usage,
+        );
+        event.emit();
+    }
+
+    /// Entity manager (or higher) refunds the escrow of a contribution to its funders.
+    pub fn cancel_contribution_escrow(
+        &mut self,
+        entity_id: AccountId,
+        contributor_id: AccountId,
+        contribution_id: u32,
+    ) {
+        self.assert_not_paused(None);
+        let mut storage_usage = env::storage_usage();
+        self.assert_capability(
+            &entity_id,
+            &env::predecessor_account_id(),
+            Capability::ApproveRequests,
+        );
+        let events = self.refund_bounty(
+            &Escrow::Contribution {
+                entity_id,
+                contributor_id,
+                contribution_id,
+            },
+            &mut storage_usage,
+        );
+        require!(!events.is_empty(), ContractError::NoEscrow.to_string());
+        Events::emit_batch(events);
+    }
+
+    // Views
+
+    /// Get the amount held in escrow, per token.
+    pub fn get_escrow(&self, escrow: Escrow) -> Vec<(Token, U128)> {
+        self.get_bounty(&escrow)
+            .map(|bounty| {
+                bounty
+                    .totals()
+                    .into_iter()
+                    .map(|(token, amount)| (token, amount.into()))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Get the amount each funder deposited in escrow, per token.
+    pub fn get_escrow_deposits(&self, escrow: Escrow) -> Vec<(Token, AccountId, U128)> {
+        self.get_bounty(&escrow)
+            .map(|bounty| {
+                bounty
+                    .deposits
+                    .into_iter()
+                    .map(|((token, account_id), amount)| (token, account_id, amount.into()))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use near_sdk::json_types::U64;
+    use near_sdk::test_utils::get_created_receipts;
+    use near_sdk::ONE_NEAR;
+
+    use crate::bounty::Escrow;
+    use crate::contributor::ContributionType;
+    use crate::test_utils::*;
+    use crate::token::Token;
+    use crate::Contract;
+
+    fn contribution_escrow(contribution_id: u32) -> Escrow {
+        Escrow::Contribution {
+            entity_id: entity(),
+            contributor_id: alice(),
+            contribution_id,
+        }
+    }
+
+    /// Contract where the owner funded alice's ongoing contribution with one NEAR.
+    fn setup_with_escrow() -> (Contract, u32) {
+        let mut contract = setup_with_entity();
+        let contribution_id = add_contribution(&mut contract, &alice());
+        set_caller_with_deposit(&owner(), ONE_NEAR);
+        contract.fund_escrow(contribution_escrow(contribution_id));
+        assert_eq!(event_names(), ["fund_escrow"]);
+        (contract, contribution_id)
+    }
+
+    #[test]
+    fn finishing_releases_escrow_to_contributor() {
+        let (mut contract, contribution_id) = setup_with_escrow();
+        assert_eq!(contract.get_escrowed_balance(Token::Near).0, ONE_NEAR);
+        set_caller(&owner());
+        contract.finish_contribution(entity(), alice(), contribution_id, U64(NOW));
+        assert_eq!(event_names(), ["finish_contribution", "release_escrow"]);
+        assert_eq!(events()[1].1["amount"], ONE_NEAR.to_string());
+        assert!(contract
+            .get_escrow(contribution_escrow(contribution_id))
+            .is_empty());
+        assert_eq!(contract.get_escrowed_balance(Token::Near).0, 0);
+        assert_eq!(get_created_receipts()[0].receiver_id, alice());
+    }
+
+    #[test]
+    fn contributor_finishing_keeps_escrow() {
+        let (mut contract, contribution_id) = setup_with_escrow();
+        set_caller(&alice());
+        contract.finish_contribution(entity(), alice(), contribution_id, U64(NOW));
+        assert_eq!(event_names(), ["finish_contribution"]);
+        assert_eq!(
+            contract.get_escrow(contribution_escrow(contribution_id)),
+            [(Token::Near, ONE_NEAR.into())]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "ERR_CONTRIBUTION_FINISHED")]
+    fn contribution_cannot_be_finished_twice() {
+        let (mut contract, contribution_id) = setup_with_escrow();
+        set_caller(&alice());
+        contract.finish_contribution(entity(), alice(), contribution_id, U64(NOW));
+        set_caller(&alice());
+        contract.finish_contribution(entity(), alice(), contribution_id, U64(NOW + 1));
+    }
+
+    #[test]
+    fn cancelling_refunds_escrow_to_funders() {
+        let (mut contract, contribution_id) = setup_with_escrow();
+        set_caller(&owner());
+        contract.cancel_contribution_escrow(entity(), alice(), contribution_id);
+        assert_eq!(event_names(), ["refund_escrow"]);
+        assert_eq!(events()[0].1["account_id"], owner().to_string());
+        assert_eq!(get_created_receipts()[0].receiver_id, owner());
+        assert_eq!(contract.get_escrowed_balance(Token::Near).0, 0);
+    }
+
+    #[test]
+    fn leaving_refunds_escrow_of_ongoing_contributions() {
+        let (mut contract, contribution_id) = setup_with_escrow();
+        set_caller(&alice());
+        contract.leave_entity(entity(), U64(NOW));
+        assert_eq!(event_names(), ["refund_escrow", "leave_entity"]);
+        assert_eq!(get_created_receipts()[0].receiver_id, owner());
+        assert!(contract
+            .get_escrow(contribution_escrow(contribution_id))
+            .is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "ERR_NO_PERMISSION")]
+    fn contributor_cannot_fund_own_escrow() {
+        let (mut contract, contribution_id) = setup_with_escrow();
+        set_caller_with_deposit(&alice(), ONE_NEAR);
+        contract.fund_escrow(contribution_escrow(contribution_id));
+    }
+
+    #[test]
+    fn need_bounty_is_released_or_refunded() {
+        let mut contract = setup_with_entity();
+        set_caller_with_deposit(&owner(), ONE_NEAR);
+        contract.post_contribution_need(
+            entity(),
+            "Need".to_string(),
+            ContributionType::Development,
+        );
+        assert_eq!(event_names(), ["post_contribution_need", "fund_escrow"]);
+        let cid = contract.get_entity_contribution_needs(entity(), None, None)[0].clone();
+        let escrow = Escrow::Need {
+            entity_id: entity(),
+            cid: cid.clone(),
+        };
+        assert_eq!(
+            contract.get_escrow(escrow.clone()),
+            [(Token::Near, ONE_NEAR.into())]
+        );
+
+        deposit_storage(&mut contract, &alice());
+        set_caller(&alice());
+        let request_id = contract.request_contribution(
+            entity(),
+            "Request".to_string(),
+            ContributionType::Development,
+            Some(cid.clone()),
+        );
+        set_caller(&owner());
+        contract.approve_contribution(request_id, None, None, None);
+        set_caller(&owner());
+        contract.finish_contribution(entity(), alice(), 0, U64(NOW));
+        assert_eq!(event_names(), ["finish_contribution", "release_escrow"]);
+        assert!(contract.get_escrow(escrow.clone()).is_empty());
+
+        set_caller_with_deposit(&owner(), ONE_NEAR);
+        contract.fund_escrow(escrow.clone());
+        set_caller(&owner());
+        let mut need = contract
+            .get_contribution_need(entity(), cid.clone())
+            .unwrap();
+        need.active = false;
+        contract.set_contribution_need(entity(), cid, need);
+        assert_eq!(event_names(), ["set_contribution_need", "refund_escrow"]);
+        assert_eq!(get_created_receipts()[0].receiver_id, owner());
+        assert!(contract.get_escrow(escrow).is_empty());
+    }
+}
@@ -3,17 +3,38 @@ use cid::Cid;
 use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
 use near_sdk::json_types::U64;
 use near_sdk::serde::{Deserialize, Serialize};
-use near_sdk::{env, near_bindgen, require, serde_json, AccountId, Timestamp};
+use near_sdk::{env, near_bindgen, require, serde_json, AccountId, StorageUsage, Timestamp};
+use schemars::JsonSchema;
 use std::collections::HashSet;
 
-use crate::contributor::{ContributionType, VersionedContributor};
+use crate::bounty::Escrow;
+use crate::contributor::ContributionType;
 use crate::dec_serde::{option_u64_dec_format, u64_dec_format};
-use crate::entity::Permission;
+use crate::entity::{Capability, Permission};
+use crate::errors::ContractError;
 use crate::events::Events;
-use crate::{Contract, ContractExt, MAX_DESCRIPTION_LENGTH};
+use crate::milestone::{Milestone, MilestoneStatus};
+use crate::pause::PauseCategory;
+use crate::storage::StorageRecord;
+use crate::token::{Payment, Token};
+use crate::{index, pagination, Contract, ContractExt};
+
+/// Whether the entity confirmed a contribution.
+#[derive(
+    BorshSerialize, BorshDeserialize, Deserialize, Serialize, PartialEq, Eq, Clone, JsonSchema,
+)]
+#[serde(crate = "near_sdk::serde")]
+pub enum Verification {
+    /// Recorded by the entity, or a claim verified by its admins.
+    Verified,
+    /// Claimed by the contributor, waiting for the entity's admins.
+    Unverified,
+    /// Claimed by the contributor and disputed by the entity's admins.
+    Disputed,
+}
 
 /// The story/description of a contribution to an entity.
-#[derive(BorshSerialize, BorshDeserialize, Deserialize, Serialize, Clone)]
+#[derive(BorshSerialize, BorshDeserialize, Deserialize, Serialize, Clone, JsonSchema)]
 #[serde(crate = "near_sdk::serde")]
 pub struct ContributionDetail {
     /// Details about this contribution.
@@ -24,45 +45,158 @@ pub struct ContributionDetail {
     pub need: Option<String>,
     /// The start date of the contribution.
     #[serde(with = "u64_dec_format")]
+    #[schemars(with = "String")]
     pub start_date: Timestamp,
     /// The end date of the contribution (if not ongoing).
     #[serde(with = "option_u64_dec_format")]
+    #[schemars(with = "Option<String>")]
+    pub end_date: Option<Timestamp>,
+    /// Whether the entity confirmed this contribution.
+    pub verification: Verification,
+    /// Payments the contributor received for this contribution, in the order they were made.
+    pub payments: Vec<Payment>,
+    /// Steps of the contribution, in order. The position of a milestone is its ID.
+    pub milestones: Vec<Milestone>,
+}
+
+/// The story/description of a contribution, as stored before contributors could claim them.
+#[derive(BorshSerialize, BorshDeserialize, Clone)]
+pub struct ContributionDetailV1 {
+    pub description: String,
+    pub contribution_type: ContributionType,
+    pub need: Option<String>,
+    pub start_date: Timestamp,
+    pub end_date: Option<Timestamp>,
+}
+
+/// Contributions were recorded by the entity, so they are verified.
+impl From<ContributionDetailV1> for ContributionDetail {
+    fn from(value: ContributionDetailV1) -> Self {
+        Self {
+            description: value.description,
+            contribution_type: value.contribution_type,
+            need: value.need,
+            start_date: value.start_date,
+            end_date: value.end_date,
+            verification: Verification::Verified,
+            payments: vec![],
+            milestones: vec![],
+        }
+    }
+}
+
+/// The story/description of a contribution, as stored before payments were recorded.
+#[derive(BorshSerialize, BorshDeserialize, Clone)]
+pub struct ContributionDetailV2 {
+    pub description: String,
+    pub contribution_type: ContributionType,
+    pub need: Option<String>,
+    pub start_date: Timestamp,
+    pub end_date: Option<Timestamp>,
+    pub verification: Verification,
+}
+
+impl From<ContributionDetailV2> for ContributionDetail {
+    fn from(value: ContributionDetailV2) -> Self {
+        Self {
+            description: value.description,
+            contribution_type: value.contribution_type,
+            need: value.need,
+            start_date: value.start_date,
+            end_date: value.end_date,
+            verification: value.verification,
+            payments: vec![],
+            milestones: vec![],
+        }
+    }
+}
+
+/// The story/description of a contribution, as stored before milestones were added.
+#[derive(BorshSerialize, BorshDeserialize, Clone)]
+pub struct ContributionDetailV3 {
+    pub description: String,
+    pub contribution_type: ContributionType,
+    pub need: Option<String>,
+    pub start_date: Timestamp,
     pub end_date: Option<Timestamp>,
+    pub verification: Verification,
+    pub payments: Vec<Payment>,
+}
+
+impl From<ContributionDetailV3> for ContributionDetail {
+    fn from(value: ContributionDetailV3) -> Self {
+        Self {
+            description: value.description,
+            contribution_type: value.contribution_type,
+            need: value.need,
+            start_date: value.start_date,
+            end_date: value.end_date,
+            verification: value.verification,
+            payments: value.payments,
+            milestones: vec![],
+        }
+    }
 }
 
 /// Relation between entity and contributor. Managed by source account.
-#[derive(BorshSerialize, BorshDeserialize, Deserialize, Serialize, Clone)]
+#[derive(BorshSerialize, BorshDeserialize, Deserialize, Serialize, Clone, JsonSchema)]
 #[serde(crate = "near_sdk::serde")]
 pub struct Contribution {
     // TODO: Do we want to store this in a UnorderedSet for lazy loading?
     /// Set of permissions this contributor has for the entity.
     pub permissions: HashSet<Permission>,
-    /// The details of the ongoing contribution.
-    pub current: ContributionDetail,
     // TODO: Do we want to keep this stored in a Vector for lazy reading?
-    /// If more than one contribution was made, previous ones are in history.
-    pub history: Vec<ContributionDetail>,
+    /// Every contribution made to the entity, ongoing or finished. The position of a contribution
+    /// is its ID, contributions are never removed from the list.
+    pub details: Vec<ContributionDetail>,
 }
 
 impl Contribution {
-    pub fn add_detail(
-        mut self,
-        start_date: u64,
-        contribution_detail: ContributionDetail,
-        permissions: Option<HashSet<Permission>>,
-    ) -> Self {
-        self.current.end_date = Some(start_date);
-        self.history.push(self.current);
-        self.current = contribution_detail;
-        self.permissions = permissions.unwrap_or(self.permissions);
-        self
+    /// Adds a contribution next to the existing ones. Returns its ID.
+    pub fn add_detail(&mut self, contribution_detail: ContributionDetail) -> u32 {
+        self.details.push(contribution_detail);
+        (self.details.len() - 1) as u32
     }
 }
 
+/// Relation between entity and contributor, as stored when only one contribution could be ongoing.
+#[derive(BorshSerialize, BorshDeserialize, Clone)]
+pub struct ContributionV1 {
+    pub permissions: HashSet<Permission>,
+    pub current: ContributionDetailV1,
+    pub history: Vec<ContributionDetailV1>,
+}
+
+/// Relation between entity and contributor, as stored before contributors could claim
+/// contributions.
+#[derive(BorshSerialize, BorshDeserialize, Clone)]
+pub struct ContributionV2 {
+    pub permissions: HashSet<Permission>,
+    pub details: Vec<ContributionDetailV1>,
+}
+
+/// Relation between entity and contributor, as stored before payments were recorded.
+#[derive(BorshSerialize, BorshDeserialize, Clone)]
+pub struct ContributionV3 {
+    pub permissions: HashSet<Permission>,
+    pub details: Vec<ContributionDetailV2>,
+}
+
+/// Relation between entity and contributor, as stored before milestones were added.
+#[derive(BorshSerialize, BorshDeserialize, Clone)]
+pub struct ContributionV4 {
+    pub permissions: HashSet<Permission>,
+    pub details: Vec<ContributionDetailV3>,
+}
+
 /// Request to contribute.
-#[derive(BorshSerialize, BorshDeserialize, Deserialize, Serialize, Clone)]
+#[derive(BorshSerialize, BorshDeserialize, Deserialize, Serialize, Clone, JsonSchema)]
 #[serde(crate = "near_sdk::serde")]
 pub struct ContributionRequest {
+    /// The entity the request was sent to.
+    pub entity_id: AccountId,
+    /// The account that sent the request.
+    pub contributor_id: AccountId,
     /// The details of the request.
     pub description: String,
     /// The type of request this is.
@@ -72,7 +206,7 @@ pub struct ContributionRequest {
 }
 
 /// A need that a entity may have.
-#[derive(BorshSerialize, BorshDeserialize, Deserialize, Serialize, Clone)]
+#[derive(BorshSerialize, BorshDeserialize, Deserialize, Serialize, Clone, JsonSchema)]
 #[serde(crate = "near_sdk::serde")]
 pub struct ContributionNeed {
     /// The details of the need.
@@ -84,7 +218,7 @@ pub struct ContributionNeed {
 }
 
 /// A invite to contribute to a entity.
-#[derive(BorshSerialize, BorshDeserialize, Deserialize, Serialize, Clone)]
+#[derive(BorshSerialize, BorshDeserialize, Deserialize, Serialize, Clone, JsonSchema)]
 #[serde(crate = "near_sdk::serde")]
 pub struct ContributionInvite {
     /// The details of the invite.
@@ -95,17 +229,45 @@ pub struct ContributionInvite {
     pub permissions: HashSet<Permission>,
     /// The start date of the contribution after accepting the invite.
     #[serde(with = "u64_dec_format")]
+    #[schemars(with = "String")]
     pub start_date: Timestamp,
 }
 
 #[derive(BorshSerialize, BorshDeserialize, Clone)]
 pub enum VersionedContribution {
+    V1(ContributionV1),
+    V2(ContributionV2),
+    V3(ContributionV3),
+    V4(ContributionV4),
     Current(Contribution),
 }
 
 impl From<VersionedContribution> for Contribution {
     fn from(value: VersionedContribution) -> Self {
         match value {
+            // The history was already in the order the contributions were made, followed by the
+            // current one.
+            VersionedContribution::V1(c) => Contribution {
+                permissions: c.permissions,
+                details: c
+                    .history
+                    .into_iter()
+                    .chain([c.current])
+                    .map(Into::into)
+                    .collect(),
+            },
+            VersionedContribution::V2(c) => Contribution {
+                permissions: c.permissions,
+                details: c.details.into_iter().map(Into::into).collect(),
+            },
+            VersionedContribution::V3(c) => Contribution {
+                permissions: c.permissions,
+                details: c.details.into_iter().map(Into::into).collect(),
+            },
+            VersionedContribution::V4(c) => Contribution {
+                permissions: c.permissions,
+                details: c.details.into_iter().map(Into::into).collect(),
+            },
             VersionedContribution::Current(c) => c,
         }
     }
@@ -159,16 +321,113 @@ pub fn create_cid(value: &str) -> String {
     cid.to_string()
 }
 
+impl Contract {
+    /// Stores the request under a new ID and indexes it. Returns the ID.
+    pub(crate) fn insert_request(&mut self, request: ContributionRequest) -> u64 {
+        let request_id = self.next_request_id;
+        self.next_request_id += 1;
+        self.index_request(request_id, &request.entity_id, &request.contributor_id);
+        self.requests
+            .insert(request_id, VersionedContributionRequest::Current(request));
+        request_id
+    }
+
+    /// Checks if the entity verified any of the contributor's contributions to it.
+    fn has_verified_contribution(&self, entity_id: &AccountId, contributor_id: &AccountId) -> bool {
+        self.contributions
+            .get(&(entity_id.clone(), contributor_id.clone()))
+            .map(|contribution| {
+                Contribution::from(contribution.clone())
+                    .details
+                    .iter()
+                    .any(|detail| detail.verification == Verification::Verified)
+            })
+            .unwrap_or_default()
+    }
+
+    /// Sets the verification of a claimed contribution, checking the caller can verify claims of
+    /// the entity. Verified contributions can't be changed.
+    fn set_verification(
+        &mut self,
+        entity_id: &AccountId,
+        contributor_id: &AccountId,
+        contribution_id: u32,
+        verification: Verification,
+    ) {
+        let mut storage_usage = env::storage_usage();
+        self.assert_capability(
+            entity_id,
+            &env::predecessor_account_id(),
+            Capability::VerifyClaims,
+        );
+        let key = (entity_id.clone(), contributor_id.clone());
+        let mut contribution: Contribution = self
+            .contributions
+            .get(&key)
+            .unwrap_or_else(|| ContractError::NoContribution.panic())
+            .clone()
+            .into();
+        let detail = contribution
+            .details
+            .get_mut(contribution_id as usize)
+            .unwrap_or_else(|| ContractError::NoContribution.panic());
+        require!(
+            detail.verification != Verification::Verified,
+            ContractError::ContributionVerified.to_string()
+        );
+        detail.verification = verification;
+        self.contributions
+            .insert(key, VersionedContribution::Current(contribution));
+        self.charge_record(
+            &StorageRecord::Contribution(entity_id.clone(), contributor_id.clone()),
+            &env::predecessor_account_id(),
+            &mut storage_usage,
+        );
+    }
+
+    /// Removes the request, refunding its storage to the requester.
+    pub(crate) fn remove_request(
+        &mut self,
+        request_id: u64,
+        entity_id: &AccountId,
+        contributor_id: &AccountId,
+        storage_usage: &mut StorageUsage,
+    ) {
+        self.release_record(&StorageRecord::Request(request_id), storage_usage, |this| {
+            this.requests.remove(&request_id);
+            this.unindex_request(request_id, entity_id, contributor_id);
+        });
+    }
+
+    fn get_request(&self, request_id: u64) -> ContributionRequest {
+        self.requests
+            .get(&request_id)
+            .unwrap_or_else(|| ContractError::NoRequest.panic())
+            .clone()
+            .into()
+    }
+}
+
 #[near_bindgen]
 impl Contract {
-    /// Create a contribution need.
+    /// Create a contribution need. An attached deposit is held as the bounty of the need.
+    #[payable]
     pub fn post_contribution_need(
         &mut self,
         entity_id: AccountId,
         description: String,
         contribution_type: ContributionType,
     ) {
-        self.assert_manager_or_higher(&entity_id, &env::predecessor_account_id());
+        self.assert_not_paused(Some(PauseCategory::Needs));
+        let mut storage_usage = env::storage_usage();
+        self.assert_capability(
+            &entity_id,
+            &env::predecessor_account_id(),
+            Capability::PostNeeds,
+        );
+        self.assert_entity_active(&entity_id);
+        self.assert_valid_description(&description);
+        self.assert_valid_contribution_type(&contribution_type);
         let need = ContributionNeed {
             description: description.clone(),
             contribution_type: contribution_type.clone(),
@@ -179,174 +438,506 @@ impl Contract {
             (entity_id.clone(), cid.clone()),
             VersionedContributionNeed::Current(need),
         );
-        Events::PostContributionNeed {
-            entity_id,
-            cid,
-            description,
-            contribution_type,
+        self.index_need(&entity_id, &cid);
+        self.charge_record(
+            &StorageRecord::Need(entity_id.clone(), cid.clone()),
+            &env::predecessor_account_id(),
+            &mut storage_usage,
+        );
+        let mut events = vec![];
+        if env::attached_deposit() > 0 {
+            let escrow = Escrow::Need {
+                entity_id: entity_id.clone(),
+                cid: cid.clone(),
+            };
+            events.push(self.add_to_bounty(
+                &escrow,
+                &env::predecessor_account_id(),
+                Token::Near,
+                env::attached_deposit(),
+                &mut storage_usage,
+            ));
+            self.charge_record(
+                &StorageRecord::Escrow(escrow),
+                &env::predecessor_account_id(),
+                &mut storage_usage,
+            );
         }
-        .emit();
+        events.insert(
+            0,
+            Events::PostContributionNeed {
+                entity_id,
+                cid,
+                description,
+                contribution_type,
+            },
+        );
+        Events::emit_batch(events);
     }
 
-    /// Update a contribution need.
+    /// Update a contribution need. Deactivating the need refunds its bounty to the funders.
     pub fn set_contribution_need(
         &mut self,
         entity_id: AccountId,
         cid: String,
         need: ContributionNeed,
     ) {
-        self.assert_manager_or_higher(&entity_id, &env::predecessor_account_id());
+        self.assert_not_paused(Some(PauseCategory::Needs));
+        let mut storage_usage = env::storage_usage();
+        self.assert_capability(
+            &entity_id,
+            &env::predecessor_account_id(),
+            Capability::PostNeeds,
+        );
+        self.assert_valid_description(&need.description);
+        self.assert_valid_contribution_type(&need.contribution_type);
         require!(
             self.needs.contains_key(&(entity_id.clone(), cid.clone())),
-            "ERR_NO_CONTRIBUTION_NEED"
+            ContractError::NoContributionNeed.to_string()
         );
-        self.needs
-            .insert((entity_id, cid), VersionedContributionNeed::Current(need));
+        let old = self
+            .needs
+            .insert(
+                (entity_id.clone(), cid.clone()),
+                VersionedContributionNeed::Current(need.clone()),
+            )
+            .unwrap_or_else(|| ContractError::NoContributionNeed.panic());
+        self.charge_record(
+            &StorageRecord::Need(entity_id.clone(), cid.clone()),
+            &env::predecessor_account_id(),
+            &mut storage_usage,
+        );
+        let refunds = if need.active {
+            vec![]
+        } else {
+            self.refund_bounty(
+                &Escrow::Need {
+                    entity_id: entity_id.clone(),
+                    cid: cid.clone(),
+                },
+                &mut storage_usage,
+            )
+        };
+        let mut events = vec![Events::SetContributionNeed {
+            entity_id,
+            cid,
+            old: old.into(),
+            new: need,
+        }];
+        events.extend(refunds);
+        Events::emit_batch(events);
     }
 
-    /// User requests to contribute to a given entity.
+    /// User requests to contribute to a given entity. A contributor can have several requests to
+    /// the same entity, but only one per need. Returns the ID of the request.
     pub fn request_contribution(
         &mut self,
         entity_id: AccountId,
         description: String,
         contribution_type: ContributionType,
         need: Option<String>,
-    ) {
-        let key = (entity_id.clone(), env::predecessor_account_id());
-        require!(
-            description.len() < MAX_DESCRIPTION_LENGTH,
-            "ERR_DESCRIPTION_TOO_LONG"
-        );
-        self.contributors
-            .entry(env::predecessor_account_id())
-            .or_insert(VersionedContributor::Current(Default::default()));
-        let need = if let Some(cid) = need {
+    ) -> U64 {
+        self.assert_not_paused(Some(PauseCategory::Requests));
+        let mut storage_usage = env::storage_usage();
+        let contributor_id = env::predecessor_account_id();
+        self.assert_entity_active(&entity_id);
+        self.assert_valid_description(&description);
+        self.assert_valid_contribution_type(&contribution_type);
+        self.add_default_contributor(&contributor_id, &mut storage_usage);
+        if let Some(cid) = &need {
             require!(
                 self.needs.contains_key(&(entity_id.clone(), cid.clone())),
-                "ERR_NO_CONTRIBUTION_NEED"
+                ContractError::NoContributionNeed.to_string()
             );
-            Some(cid)
-        } else {
-            None
-        };
-        // TODO: Check if this account already has a contribution request for this entity? Do we
-        // just overwrite or keep track of multiple requests.
-        self.requests.insert(
-            key,
-            VersionedContributionRequest::Current(ContributionRequest {
-                description: description.clone(),
-                contribution_type: contribution_type.clone(),
-                need,
-            }),
+            require!(
+                !self.check_if_need_proposed(
+                    entity_id.clone(),
+                    contributor_id.clone(),
+                    cid.clone()
+                ),
+                ContractError::NeedAlreadyProposed.to_string()
+            );
+        }
+        let request_id = self.insert_request(ContributionRequest {
+            entity_id: entity_id.clone(),
+            contributor_id: contributor_id.clone(),
+            description: description.clone(),
+            contribution_type: contribution_type.clone(),
+            need: need.clone(),
+        });
+        self.charge_record(
+            &StorageRecord::Request(request_id),
+            &contributor_id,
+            &mut storage_usage,
         );
         Events::RequestContribution {
+            request_id,
             entity_id,
-            contributor_id: env::predecessor_account_id(),
+            contributor_id,
             description,
             contribution_type,
+            need,
         }
         .emit();
+        request_id.into()
     }
 
     /// Entity manager (or higher) rejects a contribution request.
-    pub fn reject_contribution(&mut self, entity_id: AccountId, contributor_id: AccountId) {
-        self.assert_manager_or_higher(&entity_id, &env::predecessor_account_id());
-        let key = (entity_id.clone(), contributor_id.clone());
-        self.requests.remove(&key);
+    pub fn reject_contribution(&mut self, request_id: U64) {
+        self.assert_not_paused(Some(PauseCategory::Requests));
+        let mut storage_usage = env::storage_usage();
+        let request_id = request_id.into();
+        let request = self.get_request(request_id);
+        self.assert_capability(
+            &request.entity_id,
+            &env::predecessor_account_id(),
+            Capability::ApproveRequests,
+        );
+        self.remove_request(
+            request_id,
+            &request.entity_id,
+            &request.contributor_id,
+            &mut storage_usage,
+        );
         Events::RejectContribution {
-            entity_id,
-            contributor_id,
+            request_id,
+            entity_id: request.entity_id,
+            contributor_id: request.contributor_id,
+        }
+        .emit();
+    }
+
+    /// Contributor withdraws a contribution request they sent.
+    pub fn withdraw_contribution_request(&mut self, request_id: U64) {
+        self.assert_not_paused(Some(PauseCategory::Requests));
+        let mut storage_usage = env::storage_usage();
+        let request_id = request_id.into();
+        let request = self.get_request(request_id);
+        require!(
+            request.contributor_id == env::predecessor_account_id(),
+            ContractError::NoPermission.to_string()
+        );
+        self.remove_request(
+            request_id,
+            &request.entity_id,
+            &request.contributor_id,
+            &mut storage_usage,
+        );
+        Events::WithdrawContributionRequest {
+            request_id,
+            entity_id: request.entity_id,
+            contributor_id: request.contributor_id,
         }
         .emit();
     }
 
-    /// Entity manager (or higher) approves a contribution request.
+    /// Entity manager (or higher) approves a contribution request, optionally splitting the
+    /// contribution into milestones. Milestones start pending whatever status is given.
     pub fn approve_contribution(
         &mut self,
-        entity_id: AccountId,
-        contributor_id: AccountId,
+        request_id: U64,
         description: Option<String>,
         start_date: Option<U64>,
+        milestones: Option<Vec<Milestone>>,
     ) {
-        self.assert_manager_or_higher(&entity_id, &env::predecessor_account_id());
-        let key = (entity_id.clone(), contributor_id.clone());
-        let request =
-            ContributionRequest::from(self.requests.get(&key).expect("ERR_NO_REQUEST").clone());
+        self.assert_not_paused(Some(PauseCategory::Requests));
+        let mut storage_usage = env::storage_usage();
+        let request_id = request_id.into();
+        let request = self.get_request(request_id);
+        let entity_id = request.entity_id.clone();
+        let contributor_id = request.contributor_id.clone();
+        self.assert_capability(
+            &entity_id,
+            &env::predecessor_account_id(),
+            Capability::ApproveRequests,
+        );
+        if let Some(description) = &description {
+            self.assert_valid_description(description);
+        }
         let description = description.unwrap_or(request.description);
         let start_date: Timestamp = start_date.unwrap_or(env::block_timestamp().into()).into();
+        let milestones: Vec<Milestone> = milestones
+            .unwrap_or_default()
+            .into_iter()
+            .map(|milestone| self.new_milestone(milestone))
+            .collect();
         let contribution_detail = ContributionDetail {
             description: description.clone(),
             start_date,
             contribution_type: request.contribution_type.clone(),
-            need: request.need,
+            need: request.need.clone(),
             end_date: None,
+            verification: Verification::Verified,
+            payments: vec![],
+            milestones: milestones.clone(),
         };
-        self.contributors
-            .entry(contributor_id.clone())
-            .or_insert(VersionedContributor::Current(Default::default()));
+        self.remove_request(request_id, &entity_id, &contributor_id, &mut storage_usage);
+        self.add_default_contributor(&contributor_id, &mut storage_usage);
+        let key = (entity_id.clone(), contributor_id.clone());
+        let mut contribution = self
+            .contributions
+            .get(&key)
+            .map(|contribution| Contribution::from(contribution.clone()))
+            .unwrap_or(Contribution {
+                permissions: HashSet::new(),
+                details: vec![],
+            });
+        let contribution_id = contribution.add_detail(contribution_detail);
         self.contributions
-            .entry(key.clone())
-            .and_modify(|v_old| {
-                let old = Contribution::from(v_old.clone());
-                *v_old = VersionedContribution::Current(old.add_detail(
-                    start_date,
-                    contribution_detail.clone(),
-                    None,
-                ));
+            .insert(key, VersionedContribution::Current(contribution));
+        self.index_contribution(&entity_id, &contributor_id);
+        self.charge_record(
+            &StorageRecord::Contribution(entity_id.clone(), contributor_id.clone()),
+            &env::predecessor_account_id(),
+            &mut storage_usage,
+        );
+        let milestone_events: Vec<Events> = milestones
+            .into_iter()
+            .enumerate()
+            .map(|(milestone_id, milestone)| Events::AddMilestone {
+                entity_id: entity_id.clone(),
+                contributor_id: contributor_id.clone(),
+                contribution_id,
+                milestone_id: milestone_id as u32,
+                milestone,
             })
-            .or_insert(VersionedContribution::Current(Contribution {
-                permissions: HashSet::new(),
-                current: contribution_detail,
-                history: vec![],
-            }));
-        self.requests.remove(&key);
-        Events::ApproveContribution {
+            .collect();
+        let mut events = vec![Events::ApproveContribution {
+            request_id,
+            contribution_id,
             entity_id,
             contributor_id,
             description,
             contribution_type: request.contribution_type,
+            need: request.need,
             start_date,
-        }
-        .emit();
+        }];
+        events.extend(milestone_events);
+        Events::emit_batch(events);
     }
 
-    /// Entity manager (or higher) marks the contribution as finished/completed.
+    /// Entity manager (or higher), or the contributor themselves, marks the contribution as
+    /// finished/completed. Other ongoing contributions of the contributor to the entity are not
+    /// affected. When the entity finishes the contribution, the escrow of the contribution and the
+    /// bounty of its need are paid to the contributor, except for the payments of milestones that
+    /// weren't approved, which are refunded to the funders.
     pub fn finish_contribution(
         &mut self,
         entity_id: AccountId,
         contributor_id: AccountId,
+        contribution_id: u32,
         end_date: U64,
     ) {
-        self.assert_manager_or_higher(&entity_id, &env::predecessor_account_id());
+        self.assert_not_paused(None);
+        let mut storage_usage = env::storage_usage();
+        if contributor_id != env::predecessor_account_id() {
+            self.assert_capability(
+                &entity_id,
+                &env::predecessor_account_id(),
+                Capability::ApproveRequests,
+            );
+        }
         let key = (entity_id.clone(), contributor_id.clone());
-        let mut contributor: Contribution = self
+        let mut contribution: Contribution = self
             .contributions
             .get(&key)
-            .expect("ERR_NO_CONTRIBUTION")
+            .unwrap_or_else(|| ContractError::NoContribution.panic())
             .clone()
             .into();
         let end_date: Timestamp = end_date.into();
-        contributor.current.end_date = Some(end_date);
+        let detail = contribution
+            .details
+            .get_mut(contribution_id as usize)
+            .unwrap_or_else(|| ContractError::NoContribution.panic());
+        require!(
+            detail.end_date.is_none(),
+            ContractError::ContributionFinished.to_string()
+        );
+        detail.end_date = Some(end_date);
+        let need = detail.need.clone();
+        let unearned: Vec<Payment> = detail
+            .milestones
+            .iter()
+            .filter(|milestone| milestone.status != MilestoneStatus::Approved)
+            .filter_map(|milestone| milestone.payment.clone())
+            .collect();
         self.contributions
-            .insert(key, VersionedContribution::Current(contributor));
-        Events::FinishContribution {
+            .insert(key, VersionedContribution::Current(contribution));
+        self.charge_record(
+            &StorageRecord::Contribution(entity_id.clone(), contributor_id.clone()),
+            &env::predecessor_account_id(),
+            &mut storage_usage,
+        );
+        // Contributors finishing their own contribution don't get paid, the entity has to.
+        let mut releases = vec![];
+        if contributor_id != env::predecessor_account_id() {
+            let escrow = Escrow::Contribution {
+                entity_id: entity_id.clone(),
+                contributor_id: contributor_id.clone(),
+                contribution_id,
+            };
+            // Payments of milestones that weren't approved aren't earned, they go back first.
+            for payment in unearned {
+                releases.extend(self.refund_from_bounty(&escrow, payment, &mut storage_usage));
+            }
+            let escrows = need
+                .map(|cid| Escrow::Need {
+                    entity_id: entity_id.clone(),
+                    cid,
+                })
+                .into_iter()
+                .chain([escrow]);
+            for escrow in escrows {
+                releases.extend(self.release_bounty(
+                    &escrow,
+                    &contributor_id,
+                    contribution_id,
+                    &mut storage_usage,
+                ));
+            }
+        }
+        let mut events = vec![Events::FinishContribution {
             entity_id,
             contributor_id,
+            contribution_id,
+            end_date,
+        }];
+        events.extend(releases);
+        Events::emit_batch(events);
+    }
+
+    /// Contributor claims a past contribution to the entity. The claim is unverified until the
+    /// entity's admins verify it. Returns the ID of the contribution.
+    pub fn claim_contribution(
+        &mut self,
+        entity_id: AccountId,
+        description: String,
+        contribution_type: ContributionType,
+        start_date: U64,
+        end_date: U64,
+    ) -> u32 {
+        self.assert_not_paused(Some(PauseCategory::Requests));
+        let mut storage_usage = env::storage_usage();
+        let contributor_id = env::predecessor_account_id();
+        self.assert_entity_not_removing(&entity_id);
+        self.assert_valid_description(&description);
+        self.assert_valid_contribution_type(&contribution_type);
+        let start_date: Timestamp = start_date.into();
+        let end_date: Timestamp = end_date.into();
+        require!(
+            start_date <= end_date,
+            ContractError::InvalidDateRange.to_string()
+        );
+        self.add_default_contributor(&contributor_id, &mut storage_usage);
+        let key = (entity_id.clone(), contributor_id.clone());
+        let mut contribution = self
+            .contributions
+            .get(&key)
+            .map(|contribution| Contribution::from(contribution.clone()))
+            .unwrap_or(Contribution {
+                permissions: HashSet::new(),
+                details: vec![],
+            });
+        let contribution_id = contribution.add_detail(ContributionDetail {
+            description: description.clone(),
+            contribution_type: contribution_type.clone(),
+            need: None,
+            start_date,
+            end_date: Some(end_date),
+            verification: Verification::Unverified,
+            payments: vec![],
+            milestones: vec![],
+        });
+        self.contributions
+            .insert(key, VersionedContribution::Current(contribution));
+        self.index_contribution(&entity_id, &contributor_id);
+        self.charge_record(
+            &StorageRecord::Contribution(entity_id.clone(), contributor_id.clone()),
+            &contributor_id,
+            &mut storage_usage,
+        );
+        Events::ClaimContribution {
+            entity_id,
+            contributor_id,
+            contribution_id,
+            description,
+            contribution_type,
+            start_date,
             end_date,
         }
         .emit();
+        contribution_id
     }
 
-    /// Views
+    /// Entity admin (or higher) verifies a contribution claimed by the contributor.
+    pub fn verify_contribution(
+        &mut self,
+        entity_id: AccountId,
+        contributor_id: AccountId,
+        contribution_id: u32,
+    ) {
+        self.assert_not_paused(None);
+        self.set_verification(
+            &entity_id,
+            &contributor_id,
+            contribution_id,
+            Verification::Verified,
+        );
+        Events::VerifyContribution {
+            entity_id,
+            contributor_id,
+            contribution_id,
+        }
+        .emit();
+    }
 
-    /// Get all the contributions for a single contributor.
-    pub fn get_contributor_contributions(&self, account_id: AccountId) -> HashSet<AccountId> {
-        self.contributions
-            .into_iter()
-            .filter_map(|((entity, contributor), _)| {
-                (&account_id == contributor).then_some(entity.clone())
-            })
-            .collect()
+    /// Entity admin (or higher) disputes a contribution claimed by the contributor. The claim
+    /// stays on record as disputed and can still be verified later.
+    pub fn dispute_contribution(
+        &mut self,
+        entity_id: AccountId,
+        contributor_id: AccountId,
+        contribution_id: u32,
+        reason: String,
+    ) {
+        self.assert_not_paused(None);
+        self.assert_valid_description(&reason);
+        self.set_verification(
+            &entity_id,
+            &contributor_id,
+            contribution_id,
+            Verification::Disputed,
+        );
+        Events::DisputeContribution {
+            entity_id,
+            contributor_id,
+            contribution_id,
+            reason,
+        }
+        .emit();
+    }
+
+    // Views
+
+    /// Get the entities a single contributor contributes to.
+    pub fn get_contributor_contributions(
+        &self,
+        account_id: AccountId,
+        from_index: Option<U64>,
+        limit: Option<U64>,
+    ) -> Vec<AccountId> {
+        let (from_index, limit) = pagination(from_index, limit);
+        index::page(
+            &self.contributor_contributions,
+            &account_id,
+            from_index,
+            limit,
+        )
+    }
+
+    /// Get the number of contributions for a single contributor.
+    pub fn get_contributor_contributions_count(&self, account_id: AccountId) -> U64 {
+        index::len(&self.contributor_contributions, &account_id).into()
     }
 
     /// Get contribution details.
@@ -360,127 +951,255 @@ impl Contract {
             .map(|contribution| contribution.clone().into())
     }
 
-    /// Get all the contributions for this entity.
-    pub fn get_entity_contributions(&self, account_id: AccountId) -> HashSet<AccountId> {
-        self.contributions
-            .into_iter()
-            .filter_map(|((entity_id, contributor_id), _)| {
-                (entity_id == &account_id).then_some(contributor_id.clone())
+    /// Get the contributors of this entity with at least one verified contribution. Contributors
+    /// with only unverified claims are listed by `get_entity_unverified_contributions`.
+    pub fn get_entity_contributions(
+        &self,
+        account_id: AccountId,
+        from_index: Option<U64>,
+        limit: Option<U64>,
+    ) -> Vec<AccountId> {
+        let (from_index, limit) = pagination(from_index, limit);
+        let Some(contributors) = self.entity_contributions.get(&account_id) else {
+            return vec![];
+        };
+        contributors
+            .iter()
+            .filter(|contributor_id| self.has_verified_contribution(&account_id, contributor_id))
+            .skip(from_index)
+            .take(limit)
+            .cloned()
+            .collect()
+    }
+
+    /// Get the number of contributors of this entity with at least one verified contribution.
+    pub fn get_entity_contributions_count(&self, account_id: AccountId) -> U64 {
+        let Some(contributors) = self.entity_contributions.get(&account_id) else {
+            return 0.into();
+        };
+        (contributors
+            .iter()
+            .filter(|contributor_id| self.has_verified_contribution(&account_id, contributor_id))
+            .count() as u64)
+            .into()
+    }
+
+    /// Get the contributors for a specific need.
+    pub fn get_need_contributions(
+        &self,
+        account_id: AccountId,
+        cid: String,
+        from_index: Option<U64>,
+        limit: Option<U64>,
+    ) -> Vec<AccountId> {
+        let (from_index, limit) = pagination(from_index, limit);
+        let Some(contributors) = self.entity_contributions.get(&account_id) else {
+            return vec![];
+        };
+        contributors
+            .iter()
+            .filter(|contributor_id| {
+                let Some(contribution) = self
+                    .contributions
+                    .get(&(account_id.clone(), (*contributor_id).clone()))
+                else {
+                    return false;
+                };
+                Contribution::from(contribution.clone())
+                    .details
+                    .iter()
+                    .any(|detail| detail.need == Some(cid.clone()))
             })
+            .skip(from_index)
+            .take(limit)
+            .cloned()
             .collect()
     }
 
-    /// Get all contributions for a specific need.
-    pub fn get_need_contributions(&self, account_id: AccountId, cid: String) -> HashSet<AccountId> {
-        self.contributions
-            .into_iter()
-            .filter_map(|((entity_id, contributor_id), contribution)| {
-                if entity_id != &account_id {
-                    return None;
-                }
-                let contribution = Contribution::from(contribution.clone());
-                (contribution.current.need == Some(cid.clone())
-                    || contribution
-                        .history
-                        .iter()
-                        .any(|detail| detail.need == Some(cid.clone())))
-                .then_some(contributor_id.clone())
+    /// Get the claimed contributions of this entity waiting for verification, as contributor and
+    /// contribution ID.
+    pub fn get_entity_unverified_contributions(
+        &self,
+        account_id: AccountId,
+        from_index: Option<U64>,
+        limit: Option<U64>,
+    ) -> Vec<(AccountId, u32)> {
+        let (from_index, limit) = pagination(from_index, limit);
+        let Some(contributors) = self.entity_contributions.get(&account_id) else {
+            return vec![];
+        };
+        contributors
+            .iter()
+            .flat_map(|contributor_id| {
+                self.contributions
+                    .get(&(account_id.clone(), contributor_id.clone()))
+                    .map(|contribution| Contribution::from(contribution.clone()).details)
+                    .unwrap_or_default()
+                    .into_iter()
+                    .enumerate()
+                    .filter(|(_, detail)| detail.verification == Verification::Unverified)
+                    .map(|(contribution_id, _)| (contributor_id.clone(), contribution_id as u32))
             })
+            .skip(from_index)
+            .take(limit)
             .collect()
     }
 
     /// Get contribution request details.
-    pub fn get_contribution_request(
-        &self,
-        entity_id: AccountId,
-        contributor_id: AccountId,
-    ) -> Option<ContributionRequest> {
+    pub fn get_contribution_request(&self, request_id: U64) -> Option<ContributionRequest> {
         self.requests
-            .get(&(entity_id, contributor_id))
+            .get(&request_id.into())
             .map(|request| request.clone().into())
     }
 
-    /// Get all the requests this contributor sent.
+    /// Get the IDs of the requests this contributor sent.
     pub fn get_contributor_contribution_requests(
         &self,
         account_id: AccountId,
-    ) -> HashSet<AccountId> {
-        self.requests
+        from_index: Option<U64>,
+        limit: Option<U64>,
+    ) -> Vec<U64> {
+        let (from_index, limit) = pagination(from_index, limit);
+        index::page(&self.contributor_requests, &account_id, from_index, limit)
             .into_iter()
-            .filter_map(|((entity_id, contributor_id), _)| {
-                (contributor_id == &account_id).then_some(entity_id.clone())
-            })
+            .map(Into::into)
             .collect()
     }
 
-    /// Get all the requests for this entity.
-    pub fn get_entity_contribution_requests(&self, account_id: AccountId) -> HashSet<AccountId> {
-        self.requests
+    /// Get the number of requests this contributor sent.
+    pub fn get_contributor_contribution_requests_count(&self, account_id: AccountId) -> U64 {
+        index::len(&self.contributor_requests, &account_id).into()
+    }
+
+    /// Get the IDs of the requests sent to this entity.
+    pub fn get_entity_contribution_requests(
+        &self,
+        account_id: AccountId,
+        from_index: Option<U64>,
+        limit: Option<U64>,
+    ) -> Vec<U64> {
+        let (from_index, limit) = pagination(from_index, limit);
+        index::page(&self.entity_requests, &account_id, from_index, limit)
             .into_iter()
-            .filter_map(|((entity_id, contributor_id), _)| {
-                (entity_id == &account_id).then_some(contributor_id.clone())
-            })
+            .map(Into::into)
             .collect()
     }
 
-    /// Get all contribution requests this account can manage.
+    /// Get the number of requests for this entity.
+    pub fn get_entity_contribution_requests_count(&self, account_id: AccountId) -> U64 {
+        index::len(&self.entity_requests, &account_id).into()
+    }
+
+    /// Get the IDs of the contribution requests this account can manage.
     pub fn get_admin_contribution_requests(
         &self,
         account_id: AccountId,
-    ) -> Vec<(AccountId, AccountId)> {
-        self.requests
-            .into_iter()
-            .filter_map(|((entity_id, contributor_id), _)| {
-                let Some(contribution) = self.contributions.get(&(entity_id.clone(), account_id.clone())) else {
-                    return None;
-                };
-                let contribution = Contribution::from(contribution.clone());
-                contribution.permissions.contains(&Permission::Admin)
-                    .then_some((entity_id.clone(), contributor_id.clone()))
-            })
+        from_index: Option<U64>,
+        limit: Option<U64>,
+    ) -> Vec<U64> {
+        let (from_index, limit) = pagination(from_index, limit);
+        let Some(entities) = self.managed_entities.get(&account_id) else {
+            return vec![];
+        };
+        entities
+            .iter()
+            .flat_map(|entity_id| self.entity_requests.get(entity_id).into_iter().flatten())
+            .skip(from_index)
+            .take(limit)
+            .map(|request_id| (*request_id).into())
             .collect()
     }
 
-    /// Get all contribution requests for a specific need.
+    /// Get the IDs of the requests to contribute to a specific need.
     pub fn get_need_contribution_requests(
         &self,
         account_id: AccountId,
         cid: String,
-    ) -> HashSet<AccountId> {
-        self.requests
-            .into_iter()
-            .filter_map(|((entity_id, contributor_id), request)| {
-                (entity_id == &account_id
-                    && ContributionRequest::from(request.clone()).need == Some(cid.clone()))
-                .then_some(contributor_id.clone())
+        from_index: Option<U64>,
+        limit: Option<U64>,
+    ) -> Vec<U64> {
+        let (from_index, limit) = pagination(from_index, limit);
+        let Some(request_ids) = self.entity_requests.get(&account_id) else {
+            return vec![];
+        };
+        request_ids
+            .iter()
+            .filter(|request_id| {
+                self.requests
+                    .get(request_id)
+                    .map(|request| ContributionRequest::from(request.clone()).need)
+                    == Some(Some(cid.clone()))
             })
+            .skip(from_index)
+            .take(limit)
+            .map(|request_id| (*request_id).into())
             .collect()
     }
 
-    /// Get all contribution needs.
-    pub fn get_contribution_needs(&self) -> Vec<(AccountId, String)> {
+    /// Get contribution needs. By default returns the first page of them.
+    pub fn get_contribution_needs(
+        &self,
+        from_index: Option<U64>,
+        limit: Option<U64>,
+    ) -> Vec<(AccountId, String)> {
+        let (from_index, limit) = pagination(from_index, limit);
         self.needs
-            .into_iter()
-            .map(|((account_id, cid), _)| (account_id.clone(), cid.clone()))
+            .keys()
+            .skip(from_index)
+            .take(limit)
+            .cloned()
             .collect()
     }
 
-    /// Get all contribnution needs of entity.
-    pub fn get_entity_contribution_needs(&self, account_id: AccountId) -> HashSet<String> {
-        self.needs
-            .into_iter()
-            .filter_map(|((entity_id, cid), _)| (entity_id == &account_id).then_some(cid.clone()))
-            .collect()
+    /// Get the total number of contribution needs.
+    pub fn get_contribution_needs_count(&self) -> U64 {
+        (self.needs.len() as u64).into()
     }
 
-    /// Get all contribnution needs this account can manage.
-    pub fn get_admin_contribution_needs(&self, account_id: AccountId) -> Vec<(AccountId, String)> {
-        self.needs
-            .into_iter()
-            .filter_map(|((entity_id, cid), _)| {
-                self.check_is_manager_or_higher(entity_id, &account_id)
-                    .then_some((entity_id.clone(), cid.clone()))
+    /// Get contribution needs of entity.
+    pub fn get_entity_contribution_needs(
+        &self,
+        account_id: AccountId,
+        from_index: Option<U64>,
+        limit: Option<U64>,
+    ) -> Vec<String> {
+        let (from_index, limit) = pagination(from_index, limit);
+        index::page(&self.entity_needs, &account_id, from_index, limit)
+    }
+
+    /// Get the number of contribution needs of entity.
+    pub fn get_entity_contribution_needs_count(&self, account_id: AccountId) -> U64 {
+        index::len(&self.entity_needs, &account_id).into()
+    }
+
+    /// Get contribution needs this account can manage.
+    pub fn get_admin_contribution_needs(
+        &self,
+        account_id: AccountId,
+        from_index: Option<U64>,
+        limit: Option<U64>,
+    ) -> Vec<(AccountId, String)> {
+        let (from_index, limit) = pagination(from_index, limit);
+        if self.check_is_moderator(account_id.clone()) {
+            return self.get_contribution_needs(
+                Some((from_index as u64).into()),
+                Some((limit as u64).into()),
+            );
+        }
+        let Some(entities) = self.managed_entities.get(&account_id) else {
+            return vec![];
+        };
+        entities
+            .iter()
+            .flat_map(|entity_id| {
+                self.entity_needs
+                    .get(entity_id)
+                    .into_iter()
+                    .flatten()
+                    .map(|cid| (entity_id.clone(), cid.clone()))
             })
+            .skip(from_index)
+            .take(limit)
             .collect()
     }
 
@@ -503,10 +1222,50 @@ impl Contract {
         contributor_id: AccountId,
         cid: String,
     ) -> bool {
-        self.requests.iter().any(|((e_id, c_id), c)| {
-            e_id == &entity_id
-                && c_id == &contributor_id
-                && ContributionRequest::from(c.clone()).need == Some(cid.clone())
-        })
+        self.contributor_requests
+            .get(&contributor_id)
+            .into_iter()
+            .flatten()
+            .filter_map(|request_id| self.requests.get(request_id))
+            .map(|request| ContributionRequest::from(request.clone()))
+            .any(|request| request.entity_id == entity_id && request.need.as_ref() == Some(&cid))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use near_sdk::json_types::U64;
+
+    use crate::contributor::ContributionType;
+    use crate::test_utils::*;
+
+    #[test]
+    fn unverified_claims_are_listed_apart_from_contributors() {
+        let mut contract = setup_with_entity();
+        deposit_storage(&mut contract, &alice());
+        set_caller(&alice());
+        let contribution_id = contract.claim_contribution(
+            entity(),
+            "Claim".to_string(),
+            ContributionType::Development,
+            U64(0),
+            U64(NOW),
+        );
+        assert_eq!(
+            contract.get_entity_contributions(entity(), None, None),
+            [owner()]
+        );
+        assert_eq!(contract.get_entity_contributions_count(entity()).0, 1);
+        assert_eq!(
+            contract.get_entity_unverified_contributions(entity(), None, None),
+            [(alice(), contribution_id)]
+        );
+
+        set_caller(&owner());
+        contract.verify_contribution(entity(), alice(), contribution_id);
+        assert_eq!(contract.get_entity_contributions_count(entity()).0, 2);
+        assert!(contract
+            .get_entity_unverified_contributions(entity(), None, None)
+            .is_empty());
     }
 }
@@ -1,9 +1,12 @@
 use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::U64;
 use near_sdk::serde::{Deserialize, Serialize};
-use near_sdk::{assert_one_yocto, env, near_bindgen, AccountId};
+use near_sdk::{assert_one_yocto, env, near_bindgen, AccountId, StorageUsage};
+use schemars::JsonSchema;
 use std::collections::HashSet;
 
-use crate::{events::Events, Contract, ContractExt};
+use crate::pause::PauseCategory;
+use crate::{events::Events, pagination, Contract, ContractExt};
 
 /// Types of contributions a contributor is looking to do.
 #[derive(
@@ -17,6 +20,7 @@ use crate::{events::Events, Contract, ContractExt};
     Ord,
     Hash,
     Clone,
+    JsonSchema,
 )]
 #[serde(crate = "near_sdk::serde")]
 pub enum ContributionType {
@@ -35,7 +39,7 @@ impl Default for ContributionType {
 }
 
 /// Details of a contritbutor.
-#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Default)]
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Default, JsonSchema)]
 #[serde(crate = "near_sdk::serde")]
 pub struct Contributor {
     /// The types of contributions the contributor is offering.
@@ -48,6 +52,13 @@ pub struct Contributor {
     looking_for_work: bool,
 }
 
+impl Contributor {
+    /// Check if the skill is on the contributor's profile.
+    pub fn has_skill(&self, skill: &str) -> bool {
+        self.skills.contains(skill)
+    }
+}
+
 #[derive(BorshSerialize, BorshDeserialize, Clone)]
 pub enum VersionedContributor {
     Current(Contributor),
@@ -61,6 +72,26 @@ impl From<VersionedContributor> for Contributor {
     }
 }
 
+impl Contract {
+    /// Creates an empty profile for the account if it has none. The contract pays for these, so
+    /// accounts can unregister their storage once the records they paid for are removed.
+    pub(crate) fn add_default_contributor(
+        &mut self,
+        contributor_id: &AccountId,
+        storage_usage: &mut StorageUsage,
+    ) {
+        if self.contributors.contains_key(contributor_id) {
+            return;
+        }
+        self.absorb_storage(storage_usage, |this| {
+            this.contributors.insert(
+                contributor_id.clone(),
+                VersionedContributor::Current(Default::default()),
+            );
+        });
+    }
+}
+
 #[near_bindgen]
 impl Contract {
     /// Register as a contributor.
@@ -71,35 +102,66 @@ impl Contract {
         skills: HashSet<String>,
         resume: String,
     ) {
+        self.assert_not_paused(Some(PauseCategory::Registration));
         assert_one_yocto();
+        let storage_usage = env::storage_usage();
+        self.assert_valid_contributor(&contribution_types, &skills, &resume);
+        let contributor = Contributor {
+            contribution_types,
+            skills,
+            resume,
+            looking_for_work: true,
+        };
         self.contributors.insert(
             env::predecessor_account_id(),
-            VersionedContributor::Current(Contributor {
-                contribution_types,
-                skills,
-                resume,
-                looking_for_work: true,
-            }),
+            VersionedContributor::Current(contributor.clone()),
         );
+        self.charge_storage(&env::predecessor_account_id(), storage_usage);
         Events::RegisterContributor {
             contributor_id: env::predecessor_account_id(),
+            contributor,
         }
         .emit();
     }
 
     /// Edit contributor profile.
     pub fn edit_contributor(&mut self, contributor: Contributor) {
-        self.contributors.insert(
+        self.assert_not_paused(None);
+        let storage_usage = env::storage_usage();
+        self.assert_valid_contributor(
+            &contributor.contribution_types,
+            &contributor.skills,
+            &contributor.resume,
+        );
+        let old = self.contributors.insert(
             env::predecessor_account_id(),
-            VersionedContributor::Current(contributor),
+            VersionedContributor::Current(contributor.clone()),
         );
+        self.charge_storage(&env::predecessor_account_id(), storage_usage);
+        Events::EditContributor {
+            contributor_id: env::predecessor_account_id(),
+            old: old.map(Into::into),
+            new: contributor,
+        }
+        .emit();
     }
 
-    /// Views
+    // Views
+
+    /// Get contributor account IDs. By default returns the first page of them.
+    pub fn get_contributors(&self, from_index: Option<U64>, limit: Option<U64>) -> Vec<AccountId> {
+        let (from_index, limit) = pagination(from_index, limit);
+        self.contributors
+            .keys()
+            .skip(from_index)
+            .take(limit)
+            .cloned()
+            .collect()
+    }
 
-    /// Get all contributor account IDs.
-    pub fn get_contributors(&self) -> HashSet<AccountId> {
-        self.contributors.keys().cloned().collect()
+    /// Get the total number of contributors.
+    pub fn get_contributors_count(&self) -> U64 {
+        (self.contributors.len() as u64).into()
     }
 
     /// Check if account is registered as contributor.
@@ -0,0 +1,252 @@
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::{Base58CryptoHash, U64};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, near_bindgen, require, AccountId, CryptoHash, Timestamp};
+use schemars::JsonSchema;
+use std::collections::HashSet;
+
+use crate::dec_serde::u64_dec_format;
+use crate::entity::EntityKind;
+use crate::errors::ContractError;
+use crate::events::Events;
+use crate::{pagination, Contract, ContractExt};
+
+/// Sensitive actions that need the approval of `threshold` moderators.
+#[derive(BorshSerialize, BorshDeserialize, Deserialize, Serialize, Clone, JsonSchema)]
+#[serde(crate = "near_sdk::serde", tag = "action", rename_all = "snake_case")]
+pub enum ModeratorAction {
+    StageUpgrade {
+        code_hash: Base58CryptoHash,
+    },
+    SetUpgradeDelay {
+        #[serde(with = "u64_dec_format")]
+        #[schemars(with = "String")]
+        delay: Timestamp,
+    },
+    SetModerator {
+        moderator_id: AccountId,
+        old_moderator_id: Option<AccountId>,
+    },
+    AddModerator {
+        moderator_id: AccountId,
+    },
+    RemoveModerator {
+        moderator_id: AccountId,
+    },
+    SetModeratorThreshold {
+        threshold: Option<u32>,
+    },
+    AdminAddEntity {
+        account_id: AccountId,
+        founder_id: AccountId,
+        name: String,
+        kind: EntityKind,
+        #[serde(with = "u64_dec_format")]
+        #[schemars(with = "String")]
+        start_date: Timestamp,
+    },
+}
+
+impl ModeratorAction {
+    /// Identifies the action, so approvals of the same action by different moderators add up.
+    fn id(&self) -> CryptoHash {
+        env::sha256_array(&self.try_to_vec().unwrap())
+    }
+}
+
+/// Sensitive action waiting for the approval of more moderators.
+#[derive(BorshSerialize, BorshDeserialize, Deserialize, Serialize, Clone, JsonSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ModeratorProposal {
+    pub action: ModeratorAction,
+    /// Moderators that approved the action.
+    pub approvals: HashSet<AccountId>,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Clone)]
+pub enum VersionedModeratorProposal {
+    Current(ModeratorProposal),
+}
+
+impl From<VersionedModeratorProposal> for ModeratorProposal {
+    fn from(value: VersionedModeratorProposal) -> Self {
+        match value {
+            VersionedModeratorProposal::Current(p) => p,
+        }
+    }
+}
+
+impl Contract {
+    /// Number of moderator approvals sensitive actions need. A single moderator is enough when
+    /// no threshold is set.
+    fn moderator_threshold(&self) -> u32 {
+        self.moderator_threshold.unwrap_or(1)
+    }
+
+    /// Records the predecessor's approval of the action. Returns true once enough moderators
+    /// approved it, in which case the caller executes the action.
+    pub(crate) fn approve_moderator_action(&mut self, action: ModeratorAction) -> bool {
+        self.assert_moderator();
+        let moderator_id = env::predecessor_account_id();
+        let proposal_id = action.id();
+        let mut approvals = self
+            .moderator_proposals
+            .remove(&proposal_id)
+            .map(|proposal| ModeratorProposal::from(proposal).approvals)
+            .unwrap_or_default();
+        // Approvals of former moderators don't count.
+        approvals.retain(|account_id| self.moderators.contains(account_id));
+        approvals.insert(moderator_id.clone());
+        let threshold = self.moderator_threshold();
+        let is_approved = approvals.len() >= threshold as usize;
+        Events::ApproveModeratorAction {
+            proposal_id: proposal_id.into(),
+            action: action.clone(),
+            moderator_id,
+            approvals: approvals.len() as u32,
+            threshold,
+        }
+        .emit();
+        if !is_approved {
+            self.moderator_proposals.insert(
+                proposal_id,
+                VersionedModeratorProposal::Current(ModeratorProposal { action, approvals }),
+            );
+        }
+        is_approved
+    }
+
+    /// Checks that the council can still reach the threshold with the given number of members.
+    fn assert_valid_threshold(threshold: Option<u32>, moderators: u32) {
+        require!(
+            threshold.map_or(true, |threshold| (1..=moderators).contains(&threshold)),
+            ContractError::InvalidThreshold.to_string()
+        );
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Replaces `old_moderator_id` with `moderator_id` in the council, or replaces the whole
+    /// council with `moderator_id` if no old moderator is given. Needs `threshold` approvals.
+    pub fn set_moderator(&mut self, moderator_id: AccountId, old_moderator_id: Option<AccountId>) {
+        if let Some(old_moderator_id) = &old_moderator_id {
+            require!(
+                self.moderators.contains(old_moderator_id),
+                ContractError::NotModerator.to_string()
+            );
+            require!(
+                !self.moderators.contains(&moderator_id),
+                ContractError::ModeratorExists.to_string()
+            );
+        }
+        if !self.approve_moderator_action(ModeratorAction::SetModerator {
+            moderator_id: moderator_id.clone(),
+            old_moderator_id: old_moderator_id.clone(),
+        }) {
+            return;
+        }
+        let old_moderator_ids = match old_moderator_id {
+            Some(old_moderator_id) => {
+                self.moderators.remove(&old_moderator_id);
+                vec![old_moderator_id]
+            }
+            None => {
+                let old_moderator_ids = self.moderators.iter().cloned().collect();
+                self.moderators.clear();
+                self.moderator_threshold = None;
+                old_moderator_ids
+            }
+        };
+        self.moderators.insert(moderator_id.clone());
+        Events::SetModerator {
+            old_moderator_ids,
+            new_moderator_id: moderator_id,
+        }
+        .emit();
+    }
+
+    /// Adds a moderator to the council. Needs `threshold` approvals.
+    pub fn add_moderator(&mut self, moderator_id: AccountId) {
+        require!(
+            !self.moderators.contains(&moderator_id),
+            ContractError::ModeratorExists.to_string()
+        );
+        if !self.approve_moderator_action(ModeratorAction::AddModerator {
+            moderator_id: moderator_id.clone(),
+        }) {
+            return;
+        }
+        self.moderators.insert(moderator_id.clone());
+        Events::AddModerator { moderator_id }.emit();
+    }
+
+    /// Removes a moderator from the council. The council must keep at least one moderator, and
+    /// enough moderators to reach the threshold. Needs `threshold` approvals.
+    pub fn remove_moderator(&mut self, moderator_id: AccountId) {
+        require!(
+            self.moderators.contains(&moderator_id),
+            ContractError::NotModerator.to_string()
+        );
+        require!(
+            self.moderators.len() > 1,
+            ContractError::LastModerator.to_string()
+        );
+        Self::assert_valid_threshold(self.moderator_threshold, self.moderators.len() - 1);
+        if !self.approve_moderator_action(ModeratorAction::RemoveModerator {
+            moderator_id: moderator_id.clone(),
+        }) {
+            return;
+        }
+        self.moderators.remove(&moderator_id);
+        Events::RemoveModerator { moderator_id }.emit();
+    }
+
+    /// Sets the number of moderator approvals sensitive actions need. Without a threshold, a
+    /// single moderator is enough. Needs approvals under the current threshold.
+    pub fn set_moderator_threshold(&mut self, threshold: Option<u32>) {
+        Self::assert_valid_threshold(threshold, self.moderators.len());
+        if !self.approve_moderator_action(ModeratorAction::SetModeratorThreshold { threshold }) {
+            return;
+        }
+        let old = std::mem::replace(&mut self.moderator_threshold, threshold);
+        Events::SetModeratorThreshold {
+            old,
+            new: threshold,
+        }
+        .emit();
+    }
+
+    // Views
+
+    /// Get the members of the moderator council.
+    pub fn get_moderators(&self, from_index: Option<U64>, limit: Option<U64>) -> Vec<AccountId> {
+        let (from_index, limit) = pagination(from_index, limit);
+        self.moderators
+            .iter()
+            .skip(from_index)
+            .take(limit)
+            .cloned()
+            .collect()
+    }
+
+    /// Get the number of moderator approvals sensitive actions need.
+    pub fn get_moderator_threshold(&self) -> u32 {
+        self.moderator_threshold()
+    }
+
+    /// Get the sensitive actions waiting for more moderator approvals.
+    pub fn get_moderator_proposals(
+        &self,
+        from_index: Option<U64>,
+        limit: Option<U64>,
+    ) -> Vec<(Base58CryptoHash, ModeratorProposal)> {
+        let (from_index, limit) = pagination(from_index, limit);
+        self.moderator_proposals
+            .iter()
+            .skip(from_index)
+            .take(limit)
+            .map(|(proposal_id, proposal)| ((*proposal_id).into(), proposal.clone().into()))
+            .collect()
+    }
+}
@@ -2,7 +2,6 @@ pub mod u128_dec_format {
     use near_sdk::serde::de;
     use near_sdk::serde::{Deserialize, Deserializer, Serializer};
 
-    #[allow(dead_code)]
     pub fn serialize<S>(num: &u128, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: Serializer,
@@ -10,7 +9,6 @@ pub mod u128_dec_format {
         serializer.serialize_str(&num.to_string())
     }
 
-    #[allow(dead_code)]
     pub fn deserialize<'de, D>(deserializer: D) -> Result<u128, D::Error>
     where
         D: Deserializer<'de>,
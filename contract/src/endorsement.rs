@@ -0,0 +1,275 @@
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::U64;
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, near_bindgen, require, AccountId, StorageUsage, Timestamp};
+use schemars::JsonSchema;
+
+use crate::contribution::Contribution;
+use crate::contributor::Contributor;
+use crate::dec_serde::u64_dec_format;
+use crate::errors::ContractError;
+use crate::events::Events;
+use crate::pause::PauseCategory;
+use crate::storage::StorageRecord;
+use crate::{index, pagination, Contract, ContractExt};
+
+/// What an endorsement is for.
+#[derive(
+    BorshSerialize,
+    BorshDeserialize,
+    Deserialize,
+    Serialize,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Clone,
+    JsonSchema,
+)]
+#[serde(crate = "near_sdk::serde")]
+pub enum EndorsementTarget {
+    /// A skill from the contributor's profile.
+    Skill {
+        contributor_id: AccountId,
+        skill: String,
+    },
+    /// A contribution of the contributor to an entity.
+    Contribution {
+        entity_id: AccountId,
+        contributor_id: AccountId,
+        contribution_id: u32,
+    },
+}
+
+impl EndorsementTarget {
+    /// The contributor being endorsed.
+    pub fn contributor_id(&self) -> &AccountId {
+        match self {
+            EndorsementTarget::Skill { contributor_id, .. }
+            | EndorsementTarget::Contribution { contributor_id, .. } => contributor_id,
+        }
+    }
+}
+
+/// Endorsement of a contributor by another account.
+#[derive(BorshSerialize, BorshDeserialize, Deserialize, Serialize, Clone, JsonSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Endorsement {
+    /// What the endorser has to say about the target (if anything).
+    pub text: Option<String>,
+    /// When the endorsement was given.
+    #[serde(with = "u64_dec_format")]
+    #[schemars(with = "String")]
+    pub created_at: Timestamp,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Clone)]
+pub enum VersionedEndorsement {
+    Current(Endorsement),
+}
+
+impl From<VersionedEndorsement> for Endorsement {
+    fn from(value: VersionedEndorsement) -> Self {
+        match value {
+            VersionedEndorsement::Current(e) => e,
+        }
+    }
+}
+
+impl Contract {
+    /// Checks that the endorsed skill is on the contributor's profile, or that the endorsed
+    /// contribution exists.
+    fn assert_target_exists(&self, target: &EndorsementTarget) {
+        match target {
+            EndorsementTarget::Skill {
+                contributor_id,
+                skill,
+            } => {
+                let contributor = Contributor::from(
+                    self.contributors
+                        .get(contributor_id)
+                        .unwrap_or_else(|| ContractError::NotRegistered.panic())
+                        .clone(),
+                );
+                require!(
+                    contributor.has_skill(skill),
+                    ContractError::NoSkill.to_string()
+                );
+            }
+            EndorsementTarget::Contribution {
+                entity_id,
+                contributor_id,
+                contribution_id,
+            } => {
+                let contribution = self
+                    .contributions
+                    .get(&(entity_id.clone(), contributor_id.clone()))
+                    .map(|contribution| Contribution::from(contribution.clone()));
+                require!(
+                    contribution.is_some_and(
+                        |contribution| contribution.details.len() > *contribution_id as usize
+                    ),
+                    ContractError::NoContribution.to_string()
+                );
+            }
+        }
+    }
+}
+
+impl Contract {
+    /// Removes every endorsement of the target, refunding their storage to the endorsers.
+    pub(crate) fn remove_endorsements(
+        &mut self,
+        target: &EndorsementTarget,
+        storage_usage: &mut StorageUsage,
+    ) -> Vec<Events> {
+        let endorser_ids: Vec<AccountId> = self
+            .target_endorsements
+            .get(target)
+            .into_iter()
+            .flatten()
+            .cloned()
+            .collect();
+        endorser_ids
+            .into_iter()
+            .map(|endorser_id| {
+                self.release_record(
+                    &StorageRecord::Endorsement(target.clone(), endorser_id.clone()),
+                    storage_usage,
+                    |this| {
+                        this.endorsements
+                            .remove(&(target.clone(), endorser_id.clone()));
+                        this.unindex_endorsement(target, &endorser_id);
+                    },
+                );
+                Events::RemoveEndorsement {
+                    target: target.clone(),
+                    endorser_id,
+                }
+            })
+            .collect()
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Endorse a skill or a contribution of another contributor, with an optional text. Each
+    /// account can endorse a target once.
+    pub fn endorse(&mut self, target: EndorsementTarget, text: Option<String>) {
+        self.assert_not_paused(Some(PauseCategory::Endorsements));
+        let mut storage_usage = env::storage_usage();
+        let endorser_id = env::predecessor_account_id();
+        require!(
+            target.contributor_id() != &endorser_id,
+            ContractError::CannotEndorseSelf.to_string()
+        );
+        if let Some(text) = &text {
+            self.assert_valid_description(text);
+        }
+        self.assert_target_exists(&target);
+        let key = (target.clone(), endorser_id.clone());
+        require!(
+            !self.endorsements.contains_key(&key),
+            ContractError::EndorsementExists.to_string()
+        );
+        self.endorsements.insert(
+            key,
+            VersionedEndorsement::Current(Endorsement {
+                text: text.clone(),
+                created_at: env::block_timestamp(),
+            }),
+        );
+        self.index_endorsement(&target, &endorser_id);
+        self.charge_record(
+            &StorageRecord::Endorsement(target.clone(), endorser_id.clone()),
+            &endorser_id,
+            &mut storage_usage,
+        );
+        Events::Endorse {
+            target,
+            endorser_id,
+            text,
+        }
+        .emit();
+    }
+
+    /// Revoke an endorsement given before.
+    pub fn revoke_endorsement(&mut self, target: EndorsementTarget) {
+        self.assert_not_paused(None);
+        let mut storage_usage = env::storage_usage();
+        let endorser_id = env::predecessor_account_id();
+        self.release_record(
+            &StorageRecord::Endorsement(target.clone(), endorser_id.clone()),
+            &mut storage_usage,
+            |this| {
+                this.endorsements
+                    .remove(&(target.clone(), endorser_id.clone()))
+                    .unwrap_or_else(|| ContractError::NoEndorsement.panic());
+                this.unindex_endorsement(&target, &endorser_id);
+            },
+        );
+        Events::RevokeEndorsement {
+            target,
+            endorser_id,
+        }
+        .emit();
+    }
+
+    // Views
+
+    /// Get the endorsement of the target by the endorser.
+    pub fn get_endorsement(
+        &self,
+        target: EndorsementTarget,
+        endorser_id: AccountId,
+    ) -> Option<Endorsement> {
+        self.endorsements
+            .get(&(target, endorser_id))
+            .map(|endorsement| endorsement.clone().into())
+    }
+
+    /// Get the endorsements of a skill or a contribution, with their endorsers.
+    pub fn get_endorsements(
+        &self,
+        target: EndorsementTarget,
+        from_index: Option<U64>,
+        limit: Option<U64>,
+    ) -> Vec<(AccountId, Endorsement)> {
+        let (from_index, limit) = pagination(from_index, limit);
+        index::page(&self.target_endorsements, &target, from_index, limit)
+            .into_iter()
+            .filter_map(|endorser_id| {
+                let endorsement = self
+                    .endorsements
+                    .get(&(target.clone(), endorser_id.clone()))?;
+                Some((endorser_id, endorsement.clone().into()))
+            })
+            .collect()
+    }
+
+    /// Get the number of endorsements of a skill or a contribution.
+    pub fn get_endorsements_count(&self, target: EndorsementTarget) -> U64 {
+        index::len(&self.target_endorsements, &target).into()
+    }
+
+    /// Get the endorsements a contributor received, as target and endorser.
+    pub fn get_contributor_endorsements(
+        &self,
+        account_id: AccountId,
+        from_index: Option<U64>,
+        limit: Option<U64>,
+    ) -> Vec<(EndorsementTarget, AccountId)> {
+        let (from_index, limit) = pagination(from_index, limit);
+        index::page(
+            &self.contributor_endorsements,
+            &account_id,
+            from_index,
+            limit,
+        )
+    }
+
+    /// Get the number of endorsements a contributor received.
+    pub fn get_contributor_endorsements_count(&self, account_id: AccountId) -> U64 {
+        index::len(&self.contributor_endorsements, &account_id).into()
+    }
+}
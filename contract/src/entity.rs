@@ -1,31 +1,47 @@
 use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
 use near_sdk::json_types::U64;
 use near_sdk::serde::{Deserialize, Serialize};
-use near_sdk::{env, near_bindgen, AccountId, Timestamp};
+use near_sdk::{env, near_bindgen, require, AccountId, StorageUsage, Timestamp};
+use schemars::JsonSchema;
+use std::cmp::Ordering;
 use std::collections::HashSet;
 
+use crate::bounty::Escrow;
 use crate::contribution::{
-    Contribution, ContributionDetail, ContributionInvite, VersionedContribution,
-    VersionedContributionInvite,
+    Contribution, ContributionDetail, ContributionInvite, ContributionRequest, Verification,
+    VersionedContribution, VersionedContributionInvite,
 };
-use crate::contributor::{ContributionType, VersionedContributor};
+use crate::contributor::ContributionType;
+use crate::council::ModeratorAction;
 use crate::dec_serde::{option_u64_dec_format, u64_dec_format};
+use crate::endorsement::EndorsementTarget;
+use crate::errors::ContractError;
 use crate::events::Events;
-use crate::{Contract, ContractExt};
+use crate::pause::PauseCategory;
+use crate::storage::StorageRecord;
+use crate::{index, pagination, Contract, ContractExt};
 
 /// An entity can be in different states because it can potentially have an end (through different
 /// ways - legal issues, no funding...).
 /// This is represented by the EntityStatus.
-#[derive(BorshSerialize, BorshDeserialize, Deserialize, Serialize, Clone)]
+/// Only active entities can post needs, send invites and receive requests.
+#[derive(
+    BorshSerialize, BorshDeserialize, Deserialize, Serialize, PartialEq, Eq, Clone, JsonSchema,
+)]
 #[serde(crate = "near_sdk::serde")]
 pub enum EntityStatus {
     Active,
+    /// Flagged for review by the moderator.
     Flagged,
+    /// Closed by the owner, the entity has an end date.
+    Closed,
+    /// Being removed together with its needs, invites, requests and contributions.
+    Removing,
 }
 
 /// An entity can take different shapes, and currently we can categorize them in these types.
 #[allow(clippy::upper_case_acronyms)]
-#[derive(BorshSerialize, BorshDeserialize, Deserialize, Serialize, Clone)]
+#[derive(BorshSerialize, BorshDeserialize, Deserialize, Serialize, Clone, JsonSchema)]
 #[serde(crate = "near_sdk::serde")]
 pub enum EntityKind {
     Project,
@@ -36,7 +52,7 @@ pub enum EntityKind {
 /// Entity is something that is beyond a single person.
 /// Something that has a start and potentially an end.
 /// Note, that all the basic information like name, description and social information is stored in the `socialdb`.
-#[derive(BorshSerialize, BorshDeserialize, Deserialize, Serialize, Clone)]
+#[derive(BorshSerialize, BorshDeserialize, Deserialize, Serialize, Clone, JsonSchema)]
 #[serde(crate = "near_sdk::serde")]
 pub struct Entity {
     /// Name of the entity.
@@ -47,19 +63,96 @@ pub struct Entity {
     kind: EntityKind,
     /// The start date of the entity.
     #[serde(with = "u64_dec_format")]
+    #[schemars(with = "String")]
     start_date: Timestamp,
     /// The end date of the entity. (optional)
     #[serde(with = "option_u64_dec_format")]
+    #[schemars(with = "Option<String>")]
     end_date: Option<Timestamp>,
 }
 
 /// Permissions table for interaction between a contributor and an entity.
+/// Roles are ordered as Owner > Admin > Manager > Member, and each role has all the capabilities
+/// of the roles below it.
 #[derive(
-    BorshSerialize, BorshDeserialize, Deserialize, Serialize, PartialEq, Eq, PartialOrd, Hash, Clone,
+    BorshSerialize, BorshDeserialize, Deserialize, Serialize, PartialEq, Eq, Hash, Clone, JsonSchema,
 )]
 #[serde(crate = "near_sdk::serde")]
 pub enum Permission {
+    // NOTE: Variants are stored by index, so new ones must be appended. Order is given by `rank`.
     Admin,
+    Owner,
+    Manager,
+    Member,
+}
+
+/// Operations on an entity that require a role.
+#[derive(PartialEq, Eq, Clone, Copy)]
+pub enum Capability {
+    /// Post and update contribution needs.
+    PostNeeds,
+    /// Approve, reject and finish contribution requests.
+    ApproveRequests,
+    /// Invite contributors.
+    Invite,
+    /// Edit the entity details.
+    EditEntity,
+    /// Grant and revoke permissions of contributors.
+    ManagePermissions,
+    /// Close and reactivate the entity.
+    CloseEntity,
+    /// Remove the entity with all its records.
+    RemoveEntity,
+    /// Verify and dispute contributions claimed by contributors.
+    VerifyClaims,
+    /// Review finished contributions.
+    ReviewContributions,
+}
+
+impl Permission {
+    fn rank(&self) -> u8 {
+        match self {
+            Permission::Owner => 3,
+            Permission::Admin => 2,
+            Permission::Manager => 1,
+            Permission::Member => 0,
+        }
+    }
+
+    /// The lowest role that has the given capability.
+    pub fn required_for(capability: Capability) -> Self {
+        match capability {
+            Capability::PostNeeds | Capability::ApproveRequests => Permission::Manager,
+            Capability::Invite
+            | Capability::EditEntity
+            | Capability::ManagePermissions
+            | Capability::VerifyClaims
+            | Capability::ReviewContributions => Permission::Admin,
+            Capability::CloseEntity | Capability::RemoveEntity => Permission::Owner,
+        }
+    }
+
+    /// Check if this role has the given capability.
+    pub fn can(&self, capability: Capability) -> bool {
+        self >= &Self::required_for(capability)
+    }
+
+    /// The highest role in the given set of permissions.
+    pub fn highest(permissions: &HashSet<Permission>) -> Option<Permission> {
+        permissions.iter().max().cloned()
+    }
+}
+
+impl Ord for Permission {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.rank().cmp(&other.rank())
+    }
+}
+
+impl PartialOrd for Permission {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
 }
 
 #[derive(BorshSerialize, BorshDeserialize, Clone)]
@@ -77,7 +170,8 @@ impl From<VersionedEntity> for Entity {
 
 #[near_bindgen]
 impl Contract {
-    /// Add new entity and user as founding contributor.
+    /// Add new entity and user as founding contributor. Needs `threshold` moderator approvals.
+    /// Existing entities can't be added again.
     pub fn admin_add_entity(
         &mut self,
         account_id: AccountId,
@@ -86,34 +180,68 @@ impl Contract {
         kind: EntityKind,
         start_date: U64,
     ) {
-        self.assert_moderator();
+        self.assert_not_paused(Some(PauseCategory::EntityCreation));
+        let mut storage_usage = env::storage_usage();
+        self.assert_valid_name(&name);
+        if self.entities.contains_key(&account_id) {
+            ContractError::EntityExists.panic();
+        }
+        if !self.approve_moderator_action(ModeratorAction::AdminAddEntity {
+            account_id: account_id.clone(),
+            founder_id: founder_id.clone(),
+            name: name.clone(),
+            kind: kind.clone(),
+            start_date: start_date.into(),
+        }) {
+            return;
+        }
         self.entities.insert(
             account_id.clone(),
             VersionedEntity::Current(Entity {
-                name,
+                name: name.clone(),
                 status: EntityStatus::Active,
-                kind,
+                kind: kind.clone(),
                 start_date: start_date.into(),
                 end_date: None,
             }),
         );
-        self.contributors
-            .entry(founder_id.clone())
-            .or_insert(VersionedContributor::Current(Default::default()));
+        self.charge_record(
+            &StorageRecord::Entity(account_id.clone()),
+            &env::predecessor_account_id(),
+            &mut storage_usage,
+        );
+        self.add_default_contributor(&founder_id, &mut storage_usage);
         self.contributions.insert(
-            (account_id, founder_id),
+            (account_id.clone(), founder_id.clone()),
             VersionedContribution::Current(Contribution {
-                permissions: HashSet::from([Permission::Admin]),
-                current: ContributionDetail {
+                permissions: HashSet::from([Permission::Owner]),
+                details: vec![ContributionDetail {
                     description: "".to_string(),
                     start_date: start_date.into(),
                     contribution_type: ContributionType::Founding,
                     end_date: None,
                     need: None,
-                },
-                history: vec![],
+                    verification: Verification::Verified,
+                    payments: vec![],
+                    milestones: vec![],
+                }],
             }),
         );
+        self.index_contribution(&account_id, &founder_id);
+        self.index_role(&account_id, &founder_id, Some(Permission::Owner));
+        self.charge_record(
+            &StorageRecord::Contribution(account_id.clone(), founder_id.clone()),
+            &env::predecessor_account_id(),
+            &mut storage_usage,
+        );
+        Events::AddEntity {
+            entity_id: account_id,
+            founder_id,
+            name,
+            kind,
+            start_date: start_date.into(),
+        }
+        .emit();
     }
 
     /// Add new entity and given user as founding contributor.
@@ -124,47 +252,368 @@ impl Contract {
         kind: EntityKind,
         start_date: U64,
     ) {
+        self.assert_not_paused(Some(PauseCategory::EntityCreation));
+        let mut storage_usage = env::storage_usage();
+        self.assert_valid_name(&name);
         if self.entities.contains_key(&account_id) {
-            env::panic_str("ERR_ENTITY_EXISTS");
+            ContractError::EntityExists.panic();
         }
         self.entities.insert(
             account_id.clone(),
             VersionedEntity::Current(Entity {
-                name,
+                name: name.clone(),
                 status: EntityStatus::Active,
-                kind,
+                kind: kind.clone(),
                 start_date: start_date.into(),
                 end_date: None,
             }),
         );
-        self.contributors
-            .entry(env::predecessor_account_id())
-            .or_insert(VersionedContributor::Current(Default::default()));
+        self.charge_record(
+            &StorageRecord::Entity(account_id.clone()),
+            &env::predecessor_account_id(),
+            &mut storage_usage,
+        );
+        self.add_default_contributor(&env::predecessor_account_id(), &mut storage_usage);
         self.contributions.insert(
             (account_id.clone(), env::predecessor_account_id()),
             VersionedContribution::Current(Contribution {
-                permissions: HashSet::from([Permission::Admin]),
-                current: ContributionDetail {
+                permissions: HashSet::from([Permission::Owner]),
+                details: vec![ContributionDetail {
                     description: "".to_string(),
                     start_date: start_date.into(),
                     contribution_type: ContributionType::Founding,
                     end_date: None,
                     need: None,
-                },
-                history: vec![],
+                    verification: Verification::Verified,
+                    payments: vec![],
+                    milestones: vec![],
+                }],
             }),
         );
+        self.index_contribution(&account_id, &env::predecessor_account_id());
+        self.index_role(
+            &account_id,
+            &env::predecessor_account_id(),
+            Some(Permission::Owner),
+        );
+        self.charge_record(
+            &StorageRecord::Contribution(account_id.clone(), env::predecessor_account_id()),
+            &env::predecessor_account_id(),
+            &mut storage_usage,
+        );
         Events::AddEntity {
             entity_id: account_id,
+            founder_id: env::predecessor_account_id(),
+            name,
+            kind,
+            start_date: start_date.into(),
         }
         .emit();
     }
 
-    /// Moderator updates the entity details.
+    /// Entity admin (or higher) updates the entity details.
+    /// Status and end date of an existing entity only change through the lifecycle methods.
     pub fn set_entity(&mut self, account_id: AccountId, entity: Entity) {
-        self.assert_manager_or_higher(&account_id, &env::predecessor_account_id());
+        self.assert_not_paused(None);
+        let mut storage_usage = env::storage_usage();
+        let mut entity = entity;
+        self.assert_capability(
+            &account_id,
+            &env::predecessor_account_id(),
+            Capability::EditEntity,
+        );
+        self.assert_valid_name(&entity.name);
+        let old = self.get_entity(account_id.clone());
+        entity.status = old.status.clone();
+        entity.end_date = old.end_date;
+        self.entities
+            .insert(account_id.clone(), VersionedEntity::Current(entity.clone()));
+        self.charge_record(
+            &StorageRecord::Entity(account_id.clone()),
+            &env::predecessor_account_id(),
+            &mut storage_usage,
+        );
+        Events::SetEntity {
+            entity_id: account_id,
+            old: Some(old),
+            new: entity,
+        }
+        .emit();
+    }
+
+    /// Entity owner (or moderator) closes the entity with the given end date.
+    pub fn close_entity(&mut self, account_id: AccountId, end_date: U64) {
+        self.assert_not_paused(None);
+        let mut storage_usage = env::storage_usage();
+        self.assert_capability(
+            &account_id,
+            &env::predecessor_account_id(),
+            Capability::CloseEntity,
+        );
+        let mut entity = self.get_entity(account_id.clone());
+        require!(
+            entity.status != EntityStatus::Flagged,
+            ContractError::EntityFlagged.to_string()
+        );
+        require!(
+            entity.status == EntityStatus::Active,
+            ContractError::EntityNotActive.to_string()
+        );
+        entity.status = EntityStatus::Closed;
+        entity.end_date = Some(end_date.into());
+        self.entities
+            .insert(account_id.clone(), VersionedEntity::Current(entity));
+        self.charge_record(
+            &StorageRecord::Entity(account_id.clone()),
+            &env::predecessor_account_id(),
+            &mut storage_usage,
+        );
+        Events::CloseEntity {
+            entity_id: account_id,
+            end_date: end_date.into(),
+        }
+        .emit();
+    }
+
+    /// Entity owner (or moderator) reactivates a closed entity.
+    pub fn reactivate_entity(&mut self, account_id: AccountId) {
+        self.assert_not_paused(None);
+        let mut storage_usage = env::storage_usage();
+        self.assert_capability(
+            &account_id,
+            &env::predecessor_account_id(),
+            Capability::CloseEntity,
+        );
+        let mut entity = self.get_entity(account_id.clone());
+        require!(
+            entity.status == EntityStatus::Closed,
+            ContractError::EntityNotClosed.to_string()
+        );
+        entity.status = EntityStatus::Active;
+        entity.end_date = None;
+        self.entities
+            .insert(account_id.clone(), VersionedEntity::Current(entity));
+        self.charge_record(
+            &StorageRecord::Entity(account_id.clone()),
+            &env::predecessor_account_id(),
+            &mut storage_usage,
+        );
+        Events::ReactivateEntity {
+            entity_id: account_id,
+        }
+        .emit();
+    }
+
+    /// Moderator flags the entity for review.
+    pub fn flag_entity(&mut self, account_id: AccountId, reason: String) {
+        self.assert_moderator();
+        self.assert_valid_description(&reason);
+        let mut entity = self.get_entity(account_id.clone());
+        require!(
+            entity.status != EntityStatus::Flagged,
+            ContractError::EntityFlagged.to_string()
+        );
+        require!(
+            entity.status != EntityStatus::Removing,
+            ContractError::EntityRemoving.to_string()
+        );
+        entity.status = EntityStatus::Flagged;
+        self.entities
+            .insert(account_id.clone(), VersionedEntity::Current(entity));
+        Events::FlagEntity {
+            entity_id: account_id,
+            reason,
+        }
+        .emit();
+    }
+
+    /// Moderator removes the flag from the entity. The entity goes back to being closed if it has
+    /// an end date, or active otherwise.
+    pub fn unflag_entity(&mut self, account_id: AccountId, reason: String) {
+        self.assert_moderator();
+        self.assert_valid_description(&reason);
+        let mut entity = self.get_entity(account_id.clone());
+        require!(
+            entity.status == EntityStatus::Flagged,
+            ContractError::EntityNotFlagged.to_string()
+        );
+        entity.status = if entity.end_date.is_some() {
+            EntityStatus::Closed
+        } else {
+            EntityStatus::Active
+        };
         self.entities
-            .insert(account_id, VersionedEntity::Current(entity));
+            .insert(account_id.clone(), VersionedEntity::Current(entity));
+        Events::UnflagEntity {
+            entity_id: account_id,
+            reason,
+        }
+        .emit();
+    }
+
+    /// Entity owner (or moderator) removes the entity together with its needs, invites, requests
+    /// and contributions, and the reviews and endorsements of those contributions. At most
+    /// `limit` records are removed per call, so large entities are removed by calling this
+    /// repeatedly. The owner's contribution and the entity itself are removed last. Returns
+    /// whether the entity is fully removed.
+    pub fn remove_entity(&mut self, account_id: AccountId, limit: Option<U64>) -> bool {
+        self.assert_not_paused(None);
+        let mut storage_usage = env::storage_usage();
+        self.assert_capability(
+            &account_id,
+            &env::predecessor_account_id(),
+            Capability::RemoveEntity,
+        );
+        let mut entity = self.get_entity(account_id.clone());
+        if entity.status != EntityStatus::Removing {
+            entity.status = EntityStatus::Removing;
+            self.entities
+                .insert(account_id.clone(), VersionedEntity::Current(entity));
+            self.charge_record(
+                &StorageRecord::Entity(account_id.clone()),
+                &env::predecessor_account_id(),
+                &mut storage_usage,
+            );
+        }
+        let (_, mut limit) = pagination(None, limit);
+        let mut events = vec![];
+
+        for cid in index::page(&self.entity_needs, &account_id, 0, limit) {
+            self.release_record(
+                &StorageRecord::Need(account_id.clone(), cid.clone()),
+                &mut storage_usage,
+                |this| {
+                    this.needs.remove(&(account_id.clone(), cid.clone()));
+                    this.unindex_need(&account_id, &cid);
+                },
+            );
+            events.extend(self.refund_bounty(
+                &Escrow::Need {
+                    entity_id: account_id.clone(),
+                    cid: cid.clone(),
+                },
+                &mut storage_usage,
+            ));
+            events.push(Events::RemoveContributionNeed {
+                entity_id: account_id.clone(),
+                cid,
+            });
+            limit -= 1;
+        }
+        for contributor_id in index::page(&self.entity_invites, &account_id, 0, limit) {
+            self.remove_invite(&account_id, &contributor_id, &mut storage_usage);
+            events.push(Events::RemoveInvite {
+                entity_id: account_id.clone(),
+                contributor_id,
+            });
+            limit -= 1;
+        }
+        for request_id in index::page(&self.entity_requests, &account_id, 0, limit) {
+            let request = ContributionRequest::from(
+                self.requests
+                    .get(&request_id)
+                    .unwrap_or_else(|| ContractError::NoRequest.panic())
+                    .clone(),
+            );
+            self.remove_request(
+                request_id,
+                &account_id,
+                &request.contributor_id,
+                &mut storage_usage,
+            );
+            events.push(Events::RemoveContributionRequest {
+                request_id,
+                entity_id: account_id.clone(),
+                contributor_id: request.contributor_id,
+            });
+            limit -= 1;
+        }
+        // Owners keep their contribution until the end, so they can keep removing the entity.
+        let contributors: Vec<AccountId> = self
+            .entity_contributions
+            .get(&account_id)
+            .into_iter()
+            .flatten()
+            .filter(|contributor_id| {
+                self.get_role(account_id.clone(), (*contributor_id).clone())
+                    != Some(Permission::Owner)
+            })
+            .take(limit)
+            .cloned()
+            .collect();
+        limit -= contributors.len();
+        // Once only owners are left and every other phase is drained, finish the removal.
+        let remaining: Vec<AccountId> = self
+            .entity_contributions
+            .get(&account_id)
+            .into_iter()
+            .flatten()
+            .filter(|contributor_id| !contributors.contains(contributor_id))
+            .take(limit.saturating_add(1))
+            .cloned()
+            .collect();
+        let is_done = limit > 0
+            && remaining.len() <= limit
+            && remaining.iter().all(|contributor_id| {
+                self.get_role(account_id.clone(), contributor_id.clone()) == Some(Permission::Owner)
+            });
+        let removed_contributors = if is_done {
+            contributors.into_iter().chain(remaining).collect()
+        } else {
+            contributors
+        };
+        for contributor_id in removed_contributors {
+            let contribution = self.release_record(
+                &StorageRecord::Contribution(account_id.clone(), contributor_id.clone()),
+                &mut storage_usage,
+                |this| {
+                    this.unindex_contribution(&account_id, &contributor_id);
+                    this.contributions
+                        .remove(&(account_id.clone(), contributor_id.clone()))
+                        .map(Contribution::from)
+                },
+            );
+            for contribution_id in 0..contribution.map_or(0, |c| c.details.len()) {
+                events.extend(self.refund_bounty(
+                    &Escrow::Contribution {
+                        entity_id: account_id.clone(),
+                        contributor_id: contributor_id.clone(),
+                        contribution_id: contribution_id as u32,
+                    },
+                    &mut storage_usage,
+                ));
+                events.extend(self.remove_reviews(
+                    &account_id,
+                    &contributor_id,
+                    contribution_id as u32,
+                    &mut storage_usage,
+                ));
+                events.extend(self.remove_endorsements(
+                    &EndorsementTarget::Contribution {
+                        entity_id: account_id.clone(),
+                        contributor_id: contributor_id.clone(),
+                        contribution_id: contribution_id as u32,
+                    },
+                    &mut storage_usage,
+                ));
+            }
+            events.push(Events::RemoveContribution {
+                entity_id: account_id.clone(),
+                contributor_id,
+            });
+        }
+        if is_done {
+            self.release_record(
+                &StorageRecord::Entity(account_id.clone()),
+                &mut storage_usage,
+                |this| this.entities.remove(&account_id),
+            );
+            events.push(Events::RemoveEntity {
+                entity_id: account_id,
+            });
+        }
+        Events::emit_batch(events);
+        is_done
     }
 
     /// Invite a user as a contributor to an entity.
@@ -177,67 +626,104 @@ impl Contract {
         start_date: U64,
         permissions: HashSet<Permission>,
     ) {
+        self.assert_not_paused(Some(PauseCategory::Invites));
+        let mut storage_usage = env::storage_usage();
         if self
             .invites
             .contains_key(&(entity_id.clone(), contributor_id.clone()))
         {
-            env::panic_str("ERR_INVITE_EXISTS");
+            ContractError::InviteExists.panic();
+        }
+        self.assert_capability(
+            &entity_id,
+            &env::predecessor_account_id(),
+            Capability::Invite,
+        );
+        self.assert_entity_active(&entity_id);
+        self.assert_valid_description(&description);
+        self.assert_valid_contribution_type(&contribution_type);
+        if !permissions.is_empty() {
+            self.assert_capability(
+                &entity_id,
+                &env::predecessor_account_id(),
+                Capability::ManagePermissions,
+            );
+            require!(
+                !permissions.contains(&Permission::Owner),
+                ContractError::CannotGrantOwner.to_string()
+            );
         }
-        self.assert_manager_or_higher(&entity_id, &env::predecessor_account_id());
-        self.contributors
-            .entry(contributor_id.clone())
-            .or_insert(VersionedContributor::Current(Default::default()));
         self.invites.insert(
             (entity_id.clone(), contributor_id.clone()),
             VersionedContributionInvite::Current(ContributionInvite {
-                permissions,
+                permissions: permissions.clone(),
                 description: description.clone(),
                 contribution_type: contribution_type.clone(),
                 start_date: start_date.into(),
             }),
         );
+        self.index_invite(&entity_id, &contributor_id);
+        self.charge_record(
+            &StorageRecord::Invite(entity_id.clone(), contributor_id.clone()),
+            &env::predecessor_account_id(),
+            &mut storage_usage,
+        );
         Events::InviteContributor {
             entity_id,
             contributor_id,
             description,
             contribution_type,
             start_date: start_date.into(),
+            permissions,
         }
-        .emit()
+        .emit();
     }
 
     /// Accept a contribution invite from an entity with the given account ID.
     pub fn accept_invite(&mut self, account_id: AccountId) {
-        let invite = ContributionInvite::from(
-            self.invites
-                .remove(&(account_id.clone(), env::predecessor_account_id()))
-                .expect("ERR_NO_INVITE"),
-        );
-        let contribution_detail = ContributionDetail {
+        self.assert_not_paused(Some(PauseCategory::Invites));
+        let mut storage_usage = env::storage_usage();
+        let contributor_id = env::predecessor_account_id();
+        let key = (account_id.clone(), contributor_id.clone());
+        let invite = self.remove_invite(&account_id, &contributor_id, &mut storage_usage);
+        self.add_default_contributor(&contributor_id, &mut storage_usage);
+        let mut contribution = self
+            .contributions
+            .get(&key)
+            .map(|contribution| Contribution::from(contribution.clone()))
+            .unwrap_or(Contribution {
+                permissions: HashSet::new(),
+                details: vec![],
+            });
+        // Contributors who already work with the entity get the invited permissions on top of
+        // the ones they have.
+        contribution
+            .permissions
+            .extend(invite.permissions.iter().cloned());
+        let role = Permission::highest(&contribution.permissions);
+        let contribution_id = contribution.add_detail(ContributionDetail {
             description: invite.description.clone(),
             contribution_type: invite.contribution_type.clone(),
             start_date: invite.start_date,
             end_date: None,
             need: None,
-        };
+            verification: Verification::Verified,
+            payments: vec![],
+            milestones: vec![],
+        });
         self.contributions
-            .entry((account_id.clone(), env::predecessor_account_id()))
-            .and_modify(|v_old| {
-                let old = Contribution::from(v_old.clone());
-                *v_old = VersionedContribution::Current(old.add_detail(
-                    invite.start_date,
-                    contribution_detail.clone(),
-                    None,
-                ));
-            })
-            .or_insert(VersionedContribution::Current(Contribution {
-                permissions: invite.permissions.clone(),
-                current: contribution_detail.clone(),
-                history: vec![],
-            }));
+            .insert(key, VersionedContribution::Current(contribution));
+        self.index_contribution(&account_id, &contributor_id);
+        self.index_role(&account_id, &contributor_id, role);
+        self.charge_record(
+            &StorageRecord::Contribution(account_id.clone(), contributor_id.clone()),
+            &contributor_id,
+            &mut storage_usage,
+        );
         Events::AcceptInvite {
             entity_id: account_id,
-            contributor_id: env::predecessor_account_id(),
+            contributor_id,
+            contribution_id,
             description: invite.description,
             contribution_type: invite.contribution_type,
             start_date: invite.start_date,
@@ -247,60 +733,444 @@ impl Contract {
 
     /// Reject a contribution inivte from an entity with the given account ID.
     pub fn reject_invite(&mut self, account_id: AccountId) {
-        self.invites
-            .remove(&(account_id, env::predecessor_account_id()))
-            .expect("ERR_NO_INVITE");
+        self.assert_not_paused(Some(PauseCategory::Invites));
+        let mut storage_usage = env::storage_usage();
+        let contributor_id = env::predecessor_account_id();
+        self.remove_invite(&account_id, &contributor_id, &mut storage_usage);
+        Events::RejectInvite {
+            entity_id: account_id,
+            contributor_id,
+        }
+        .emit();
     }
 
-    /// Views
+    /// Entity admin (or higher) cancels an invite the entity sent that wasn't answered yet.
+    pub fn cancel_invite(&mut self, entity_id: AccountId, contributor_id: AccountId) {
+        self.assert_not_paused(Some(PauseCategory::Invites));
+        let mut storage_usage = env::storage_usage();
+        self.assert_capability(
+            &entity_id,
+            &env::predecessor_account_id(),
+            Capability::Invite,
+        );
+        self.remove_invite(&entity_id, &contributor_id, &mut storage_usage);
+        Events::CancelInvite {
+            entity_id,
+            contributor_id,
+        }
+        .emit();
+    }
 
-    /// List out entities. By default list all of them.
-    pub fn get_entities(&self) -> HashSet<AccountId> {
-        self.entities.keys().cloned().collect()
+    /// Contributor leaves the entity: their ongoing contributions end at the given date, escrows
+    /// of those contributions are refunded to their funders and they lose their permissions.
+    /// Their past contributions stay on record. Owners have to transfer the ownership first, and
+    /// the last admin of an entity without an owner can't leave.
+    pub fn leave_entity(&mut self, entity_id: AccountId, end_date: U64) {
+        self.assert_not_paused(None);
+        let mut storage_usage = env::storage_usage();
+        let contributor_id = env::predecessor_account_id();
+        let key = (entity_id.clone(), contributor_id.clone());
+        let mut contribution = Contribution::from(
+            self.contributions
+                .get(&key)
+                .unwrap_or_else(|| ContractError::NoContribution.panic())
+                .clone(),
+        );
+        require!(
+            !contribution.permissions.contains(&Permission::Owner),
+            ContractError::OwnerCannotLeave.to_string()
+        );
+        let was_admin = Permission::highest(&contribution.permissions) >= Some(Permission::Admin);
+        let end_date: Timestamp = end_date.into();
+        let mut ended = vec![];
+        for (contribution_id, detail) in contribution.details.iter_mut().enumerate() {
+            if detail.end_date.is_none() {
+                detail.end_date = Some(end_date);
+                ended.push(contribution_id as u32);
+            }
+        }
+        contribution.permissions.clear();
+        self.contributions
+            .insert(key, VersionedContribution::Current(contribution));
+        self.index_role(&entity_id, &contributor_id, None);
+        if was_admin && !self.has_owner(&entity_id) {
+            require!(
+                self.count_admins(&entity_id) > 0,
+                ContractError::LastAdmin.to_string()
+            );
+        }
+        self.charge_record(
+            &StorageRecord::Contribution(entity_id.clone(), contributor_id.clone()),
+            &contributor_id,
+            &mut storage_usage,
+        );
+        let mut events = vec![];
+        // Nobody can release an escrow of an ended contribution anymore, give it back.
+        for contribution_id in ended {
+            events.extend(self.refund_bounty(
+                &Escrow::Contribution {
+                    entity_id: entity_id.clone(),
+                    contributor_id: contributor_id.clone(),
+                    contribution_id,
+                },
+                &mut storage_usage,
+            ));
+        }
+        events.push(Events::LeaveEntity {
+            entity_id,
+            contributor_id,
+            end_date,
+        });
+        Events::emit_batch(events);
     }
 
-    /// List out entities that account ID is admin for.
-    pub fn get_admin_entities(&self, account_id: AccountId) -> HashSet<AccountId> {
+    /// Entity admin (or higher) grants a permission to a contributor of the entity.
+    pub fn grant_permission(
+        &mut self,
+        entity_id: AccountId,
+        contributor_id: AccountId,
+        permission: Permission,
+    ) {
+        self.assert_not_paused(None);
+        let mut storage_usage = env::storage_usage();
+        self.assert_capability(
+            &entity_id,
+            &env::predecessor_account_id(),
+            Capability::ManagePermissions,
+        );
+        require!(
+            permission != Permission::Owner,
+            ContractError::CannotGrantOwner.to_string()
+        );
+        self.assert_outranks(&entity_id, &env::predecessor_account_id(), &permission);
+        let mut permissions = self.get_permissions(&entity_id, &contributor_id);
+        permissions.insert(permission.clone());
+        self.set_permissions(&entity_id, &contributor_id, permissions, &mut storage_usage);
+        Events::GrantPermission {
+            entity_id,
+            contributor_id,
+            permission,
+        }
+        .emit();
+    }
+
+    /// Entity admin (or higher) revokes a permission from a contributor of the entity.
+    /// The last admin of an entity can't be removed.
+    pub fn revoke_permission(
+        &mut self,
+        entity_id: AccountId,
+        contributor_id: AccountId,
+        permission: Permission,
+    ) {
+        self.assert_not_paused(None);
+        let mut storage_usage = env::storage_usage();
+        self.assert_capability(
+            &entity_id,
+            &env::predecessor_account_id(),
+            Capability::ManagePermissions,
+        );
+        require!(
+            permission != Permission::Owner,
+            ContractError::CannotRevokeOwner.to_string()
+        );
+        if let Some(role) = self.get_role(entity_id.clone(), contributor_id.clone()) {
+            self.assert_outranks(&entity_id, &env::predecessor_account_id(), &role);
+        }
+        let mut permissions = self.get_permissions(&entity_id, &contributor_id);
+        require!(
+            permissions.remove(&permission),
+            ContractError::NoPermissionToRevoke.to_string()
+        );
+        self.set_permissions(&entity_id, &contributor_id, permissions, &mut storage_usage);
+        require!(
+            self.count_admins(&entity_id) > 0,
+            ContractError::LastAdmin.to_string()
+        );
+        Events::RevokePermission {
+            entity_id,
+            contributor_id,
+            permission,
+        }
+        .emit();
+    }
+
+    /// Entity owner (or moderator) hands the ownership of the entity to another contributor.
+    /// Previous owners stay on as admins.
+    pub fn transfer_ownership(&mut self, entity_id: AccountId, owner_id: AccountId) {
+        self.assert_not_paused(None);
+        let mut storage_usage = env::storage_usage();
+        let account_id = env::predecessor_account_id();
+        require!(
+            self.check_is_moderator(account_id.clone())
+                || self.get_role(entity_id.clone(), account_id) == Some(Permission::Owner),
+            ContractError::OnlyOwner.to_string()
+        );
+        let mut permissions = self.get_permissions(&entity_id, &owner_id);
+        let owners: Vec<AccountId> = self
+            .entity_contributions
+            .get(&entity_id)
+            .into_iter()
+            .flatten()
+            .filter(|contributor_id| {
+                self.get_role(entity_id.clone(), (*contributor_id).clone())
+                    == Some(Permission::Owner)
+            })
+            .cloned()
+            .collect();
+        for previous_owner_id in owners {
+            let mut previous = self.get_permissions(&entity_id, &previous_owner_id);
+            previous.remove(&Permission::Owner);
+            previous.insert(Permission::Admin);
+            self.set_permissions(&entity_id, &previous_owner_id, previous, &mut storage_usage);
+        }
+        permissions.insert(Permission::Owner);
+        self.set_permissions(&entity_id, &owner_id, permissions, &mut storage_usage);
+        Events::TransferOwnership {
+            entity_id,
+            owner_id,
+        }
+        .emit();
+    }
+
+    /// Checks that the entity exists and is active.
+    pub(crate) fn assert_entity_active(&self, entity_id: &AccountId) {
+        let entity = Entity::from(
+            self.entities
+                .get(entity_id)
+                .unwrap_or_else(|| ContractError::NoEntity.panic())
+                .clone(),
+        );
+        require!(
+            entity.status == EntityStatus::Active,
+            ContractError::EntityNotActive.to_string()
+        );
+    }
+
+    /// Checks that the entity exists and isn't being removed.
+    pub(crate) fn assert_entity_not_removing(&self, entity_id: &AccountId) {
+        require!(
+            self.get_entity(entity_id.clone()).status != EntityStatus::Removing,
+            ContractError::EntityRemoving.to_string()
+        );
+    }
+
+    /// Whether the entity exists and isn't closed or being removed.
+    pub(crate) fn is_entity_open(&self, entity_id: &AccountId) -> bool {
+        self.entities
+            .get(entity_id)
+            .map(|entity| {
+                !matches!(
+                    Entity::from(entity.clone()).status,
+                    EntityStatus::Closed | EntityStatus::Removing
+                )
+            })
+            .unwrap_or_default()
+    }
+
+    /// Checks that the account's role is at least as high as the given role. Moderator outranks
+    /// everyone.
+    fn assert_outranks(&self, entity_id: &AccountId, account_id: &AccountId, role: &Permission) {
+        require!(
+            self.check_is_moderator(account_id.clone())
+                || self
+                    .get_role(entity_id.clone(), account_id.clone())
+                    .as_ref()
+                    >= Some(role),
+            ContractError::NoPermission.to_string()
+        );
+    }
+
+    /// Get the permissions of an existing contribution.
+    fn get_permissions(
+        &self,
+        entity_id: &AccountId,
+        contributor_id: &AccountId,
+    ) -> HashSet<Permission> {
+        Contribution::from(
+            self.contributions
+                .get(&(entity_id.clone(), contributor_id.clone()))
+                .unwrap_or_else(|| ContractError::NoContribution.panic())
+                .clone(),
+        )
+        .permissions
+    }
+
+    /// Replace the permissions of an existing contribution and update the indexes.
+    fn set_permissions(
+        &mut self,
+        entity_id: &AccountId,
+        contributor_id: &AccountId,
+        permissions: HashSet<Permission>,
+        storage_usage: &mut StorageUsage,
+    ) {
+        let role = Permission::highest(&permissions);
+        let key = (entity_id.clone(), contributor_id.clone());
+        let mut contribution = Contribution::from(
+            self.contributions
+                .get(&key)
+                .unwrap_or_else(|| ContractError::NoContribution.panic())
+                .clone(),
+        );
+        contribution.permissions = permissions;
         self.contributions
+            .insert(key, VersionedContribution::Current(contribution));
+        self.index_role(entity_id, contributor_id, role);
+        self.charge_record(
+            &StorageRecord::Contribution(entity_id.clone(), contributor_id.clone()),
+            &env::predecessor_account_id(),
+            storage_usage,
+        );
+    }
+
+    /// Removes the invite, refunding its storage to the inviter.
+    fn remove_invite(
+        &mut self,
+        entity_id: &AccountId,
+        contributor_id: &AccountId,
+        storage_usage: &mut StorageUsage,
+    ) -> ContributionInvite {
+        self.release_record(
+            &StorageRecord::Invite(entity_id.clone(), contributor_id.clone()),
+            storage_usage,
+            |this| {
+                let invite = this
+                    .invites
+                    .remove(&(entity_id.clone(), contributor_id.clone()))
+                    .unwrap_or_else(|| ContractError::NoInvite.panic());
+                this.unindex_invite(entity_id, contributor_id);
+                invite.into()
+            },
+        )
+    }
+
+    /// Count the contributors that are admins (or higher) of the entity.
+    fn count_admins(&self, entity_id: &AccountId) -> usize {
+        self.entity_contributions
+            .get(entity_id)
+            .into_iter()
+            .flatten()
+            .filter(|contributor_id| {
+                self.get_role(entity_id.clone(), (*contributor_id).clone())
+                    >= Some(Permission::Admin)
+            })
+            .count()
+    }
+
+    /// Whether a contributor of the entity is its owner.
+    pub(crate) fn has_owner(&self, entity_id: &AccountId) -> bool {
+        self.entity_contributions
+            .get(entity_id)
             .into_iter()
-            .filter_map(|((entity_id, contributor_id), contribution)| {
-                (contributor_id == &account_id
-                    && Contribution::from(contribution.clone())
-                        .permissions
-                        .contains(&Permission::Admin))
-                .then_some(entity_id.clone())
+            .flatten()
+            .any(|contributor_id| {
+                self.get_role(entity_id.clone(), contributor_id.clone()) == Some(Permission::Owner)
+            })
+    }
+
+    // Views
+
+    /// List out entities. By default list the first page of them.
+    pub fn get_entities(&self, from_index: Option<U64>, limit: Option<U64>) -> Vec<AccountId> {
+        let (from_index, limit) = pagination(from_index, limit);
+        self.entities
+            .keys()
+            .skip(from_index)
+            .take(limit)
+            .cloned()
+            .collect()
+    }
+
+    /// Get the total number of entities.
+    pub fn get_entities_count(&self) -> U64 {
+        (self.entities.len() as u64).into()
+    }
+
+    /// List out entities that account ID is admin (or higher) for.
+    pub fn get_admin_entities(
+        &self,
+        account_id: AccountId,
+        from_index: Option<U64>,
+        limit: Option<U64>,
+    ) -> Vec<AccountId> {
+        let (from_index, limit) = pagination(from_index, limit);
+        let Some(entities) = self.managed_entities.get(&account_id) else {
+            return vec![];
+        };
+        entities
+            .iter()
+            .filter(|entity_id| {
+                self.get_role((*entity_id).clone(), account_id.clone()) >= Some(Permission::Admin)
             })
+            .skip(from_index)
+            .take(limit)
+            .cloned()
             .collect()
     }
 
+    /// List out entities that account ID is manager (or higher) for.
+    pub fn get_managed_entities(
+        &self,
+        account_id: AccountId,
+        from_index: Option<U64>,
+        limit: Option<U64>,
+    ) -> Vec<AccountId> {
+        let (from_index, limit) = pagination(from_index, limit);
+        index::page(&self.managed_entities, &account_id, from_index, limit)
+    }
+
+    /// Get the highest role the account has in the entity (if any).
+    pub fn get_role(&self, entity_id: AccountId, account_id: AccountId) -> Option<Permission> {
+        self.contributions
+            .get(&(entity_id, account_id))
+            .and_then(|contribution| {
+                Permission::highest(&Contribution::from(contribution.clone()).permissions)
+            })
+    }
+
     /// List single entity details.
     pub fn get_entity(&self, account_id: AccountId) -> Entity {
         self.entities
             .get(&account_id)
-            .expect("ERR_NO_ENTITY")
+            .unwrap_or_else(|| ContractError::NoEntity.panic())
             .clone()
             .into()
     }
 
     /// List entity founders.
-    pub fn get_founders(&self, account_id: AccountId) -> HashSet<AccountId> {
-        self.contributions
-            .into_iter()
-            .filter_map(|((entity_id, contributor_id), contribution)| {
-                (entity_id == &account_id && {
-                    let contribution = Contribution::from(contribution.clone());
-                    let founding_type = vec![
-                        ContributionType::Founding,
-                        ContributionType::Other("Founding".to_string()),
-                    ];
-                    founding_type.contains(&contribution.current.contribution_type)
-                        || contribution
-                            .history
-                            .into_iter()
-                            .any(|detail| founding_type.contains(&detail.contribution_type))
-                })
-                .then_some(contributor_id.clone())
+    pub fn get_founders(
+        &self,
+        account_id: AccountId,
+        from_index: Option<U64>,
+        limit: Option<U64>,
+    ) -> Vec<AccountId> {
+        let (from_index, limit) = pagination(from_index, limit);
+        let Some(contributors) = self.entity_contributions.get(&account_id) else {
+            return vec![];
+        };
+        let founding_type = [
+            ContributionType::Founding,
+            ContributionType::Other("Founding".to_string()),
+        ];
+        contributors
+            .iter()
+            .filter(|contributor_id| {
+                let Some(contribution) = self
+                    .contributions
+                    .get(&(account_id.clone(), (*contributor_id).clone()))
+                else {
+                    return false;
+                };
+                Contribution::from(contribution.clone())
+                    .details
+                    .into_iter()
+                    .any(|detail| {
+                        detail.verification == Verification::Verified
+                            && founding_type.contains(&detail.contribution_type)
+                    })
             })
+            .skip(from_index)
+            .take(limit)
+            .cloned()
             .collect()
     }
 
@@ -310,23 +1180,35 @@ impl Contract {
     }
 
     /// List invites sent by entity with given account ID.
-    pub fn get_entity_invites(&self, account_id: AccountId) -> HashSet<AccountId> {
-        self.invites
-            .into_iter()
-            .filter_map(|((entity_id, contributor_id), _)| {
-                (entity_id == &account_id).then_some(contributor_id.clone())
-            })
-            .collect()
+    pub fn get_entity_invites(
+        &self,
+        account_id: AccountId,
+        from_index: Option<U64>,
+        limit: Option<U64>,
+    ) -> Vec<AccountId> {
+        let (from_index, limit) = pagination(from_index, limit);
+        index::page(&self.entity_invites, &account_id, from_index, limit)
+    }
+
+    /// Get the number of invites sent by entity with given account ID.
+    pub fn get_entity_invites_count(&self, account_id: AccountId) -> U64 {
+        index::len(&self.entity_invites, &account_id).into()
     }
 
     /// List invites sent to contributor with given account ID.
-    pub fn get_contributor_invites(&self, account_id: AccountId) -> HashSet<AccountId> {
-        self.invites
-            .into_iter()
-            .filter_map(|((entity_id, contributor_id), _)| {
-                (contributor_id == &account_id).then_some(entity_id.clone())
-            })
-            .collect()
+    pub fn get_contributor_invites(
+        &self,
+        account_id: AccountId,
+        from_index: Option<U64>,
+        limit: Option<U64>,
+    ) -> Vec<AccountId> {
+        let (from_index, limit) = pagination(from_index, limit);
+        index::page(&self.contributor_invites, &account_id, from_index, limit)
+    }
+
+    /// Get the number of invites sent to contributor with given account ID.
+    pub fn get_contributor_invites_count(&self, account_id: AccountId) -> U64 {
+        index::len(&self.contributor_invites, &account_id).into()
     }
 
     /// Get invite details for entity and contributor with given IDs.
@@ -340,3 +1222,202 @@ impl Contract {
             .map(|invite| invite.clone().into())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use near_contract_standards::storage_management::StorageManagement;
+    use near_sdk::env;
+    use near_sdk::json_types::U64;
+    use std::collections::HashSet;
+
+    use crate::contributor::ContributionType;
+    use crate::endorsement::EndorsementTarget;
+    use crate::entity::{EntityKind, Permission};
+    use crate::test_utils::*;
+    use crate::Contract;
+
+    /// Contract where alice contributes to the entity with the given permission.
+    fn setup_with_role(permission: Permission) -> Contract {
+        let mut contract = setup_with_entity();
+        add_contribution(&mut contract, &alice());
+        grant(&mut contract, &alice(), permission);
+        contract
+    }
+
+    fn invite_bob(contract: &mut Contract) {
+        contract.invite_contributor(
+            entity(),
+            bob(),
+            "Invite".to_string(),
+            ContributionType::Development,
+            U64(NOW),
+            HashSet::new(),
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "ERR_NO_PERMISSION")]
+    fn member_cannot_post_needs() {
+        let mut contract = setup_with_role(Permission::Member);
+        set_caller(&alice());
+        contract.post_contribution_need(
+            entity(),
+            "Need".to_string(),
+            ContributionType::Development,
+        );
+    }
+
+    #[test]
+    fn manager_can_post_needs() {
+        let mut contract = setup_with_role(Permission::Manager);
+        set_caller(&alice());
+        contract.post_contribution_need(
+            entity(),
+            "Need".to_string(),
+            ContributionType::Development,
+        );
+        assert_eq!(contract.get_entity_contribution_needs_count(entity()).0, 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "ERR_NO_PERMISSION")]
+    fn manager_cannot_invite() {
+        let mut contract = setup_with_role(Permission::Manager);
+        set_caller(&alice());
+        invite_bob(&mut contract);
+    }
+
+    #[test]
+    fn admin_can_invite() {
+        let mut contract = setup_with_role(Permission::Admin);
+        set_caller(&alice());
+        invite_bob(&mut contract);
+        assert!(contract.get_invite(entity(), bob()).is_some());
+    }
+
+    #[test]
+    #[should_panic(expected = "ERR_NO_PERMISSION")]
+    fn admin_cannot_close_entity() {
+        let mut contract = setup_with_role(Permission::Admin);
+        set_caller(&alice());
+        contract.close_entity(entity(), U64(NOW));
+    }
+
+    #[test]
+    #[should_panic(expected = "ERR_NO_PERMISSION")]
+    fn admin_cannot_revoke_owner() {
+        let mut contract = setup_with_role(Permission::Admin);
+        set_caller(&alice());
+        contract.revoke_permission(entity(), owner(), Permission::Admin);
+    }
+
+    #[test]
+    #[should_panic(expected = "ERR_NO_PERMISSION")]
+    fn outsider_cannot_edit_entity() {
+        let mut contract = setup_with_entity();
+        set_caller(&bob());
+        contract.set_entity(entity(), contract.get_entity(entity()));
+    }
+
+    #[test]
+    fn moderator_has_every_capability() {
+        let mut contract = setup_with_entity();
+        set_caller(&moderator());
+        invite_bob(&mut contract);
+        set_caller(&moderator());
+        contract.close_entity(entity(), U64(NOW));
+        assert!(contract.get_invite(entity(), bob()).is_some());
+        assert!(contract.get_entity(entity()).end_date.is_some());
+    }
+
+    #[test]
+    #[should_panic(expected = "ERR_NO_ENTITY")]
+    fn set_entity_does_not_create_entities() {
+        let mut contract = setup_with_entity();
+        let entity = contract.get_entity(entity());
+        set_caller(&moderator());
+        contract.set_entity(bob(), entity);
+    }
+
+    #[test]
+    fn removing_entity_removes_endorsements_of_its_contributions() {
+        let mut contract = setup_with_entity();
+        let contribution_id = add_contribution(&mut contract, &alice());
+        let target = EndorsementTarget::Contribution {
+            entity_id: entity(),
+            contributor_id: alice(),
+            contribution_id,
+        };
+        deposit_storage(&mut contract, &bob());
+        let balance = contract.storage_balance_of(bob()).unwrap();
+        set_caller(&bob());
+        contract.endorse(target.clone(), None);
+        set_caller(&owner());
+        assert!(contract.remove_entity(entity(), None));
+        assert!(contract.get_endorsement(target.clone(), bob()).is_none());
+        assert_eq!(contract.get_endorsements_count(target).0, 0);
+        assert_eq!(contract.get_contributor_endorsements_count(alice()).0, 0);
+        assert_eq!(
+            contract.storage_balance_of(bob()).unwrap().available,
+            balance.available
+        );
+    }
+
+    #[test]
+    fn accepted_invite_adds_permissions_to_existing_contribution() {
+        let mut contract = setup_with_role(Permission::Member);
+        set_caller(&owner());
+        contract.invite_contributor(
+            entity(),
+            alice(),
+            "Invite".to_string(),
+            ContributionType::Development,
+            U64(NOW),
+            HashSet::from([Permission::Manager]),
+        );
+        set_caller(&alice());
+        contract.accept_invite(entity());
+        assert!(contract.get_role(entity(), alice()) == Some(Permission::Manager));
+        assert_eq!(
+            contract.get_managed_entities(alice(), None, None),
+            [entity()]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "ERR_LAST_ADMIN")]
+    fn last_admin_cannot_leave_entity_without_owner() {
+        let mut contract = setup_with_entity();
+        set_caller(&moderator());
+        contract.set_permissions(
+            &entity(),
+            &owner(),
+            HashSet::from([Permission::Admin]),
+            &mut env::storage_usage(),
+        );
+        set_caller(&owner());
+        contract.leave_entity(entity(), U64(NOW));
+    }
+
+    #[test]
+    fn entity_is_removed_with_the_largest_limit() {
+        let mut contract = setup_with_entity();
+        add_contribution(&mut contract, &alice());
+        set_caller(&owner());
+        assert!(contract.remove_entity(entity(), Some(U64(u64::MAX))));
+    }
+
+    #[test]
+    #[should_panic(expected = "ERR_ENTITY_EXISTS")]
+    fn moderator_cannot_add_existing_entity() {
+        let mut contract = setup_with_entity();
+        set_caller(&moderator());
+        contract.admin_add_entity(
+            entity(),
+            bob(),
+            "Entity".to_string(),
+            EntityKind::Project,
+            U64(NOW),
+        );
+    }
+}
@@ -0,0 +1,169 @@
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, near_bindgen};
+use schemars::JsonSchema;
+use std::fmt;
+
+use crate::{Contract, ContractExt};
+
+/// Defines the `ContractError` enum together with its stable code, name and message.
+macro_rules! contract_errors {
+    ($($variant:ident = $code:literal, $name:literal, $message:literal;)*) => {
+        /// Errors the contract fails with. Codes are stable and must never be reused.
+        #[derive(Clone, Copy, PartialEq, Eq, Debug)]
+        pub enum ContractError {
+            $($variant),*
+        }
+
+        impl ContractError {
+            /// Every error the contract can fail with.
+            pub const ALL: &'static [ContractError] = &[$(ContractError::$variant),*];
+
+            /// Stable numeric code of the error.
+            pub fn code(&self) -> u32 {
+                match self {
+                    $(ContractError::$variant => $code),*
+                }
+            }
+
+            /// Stable name of the error.
+            pub fn name(&self) -> &'static str {
+                match self {
+                    $(ContractError::$variant => $name),*
+                }
+            }
+
+            /// Human readable description of the error.
+            pub fn message(&self) -> &'static str {
+                match self {
+                    $(ContractError::$variant => $message),*
+                }
+            }
+        }
+    };
+}
+
+contract_errors! {
+    NotInitialized = 1, "ERR_NOT_INITIALIZED", "Contract is not initialized";
+    OnlyModerator = 2, "ERR_ONLY_MODERATOR", "Only the moderator can call this method";
+    NoPermission = 3, "ERR_NO_PERMISSION", "Account doesn't have the permission for this operation";
+    NotRegistered = 4, "ERR_NOT_REGISTERED", "Account is not registered as a contributor";
+    EntityExists = 5, "ERR_ENTITY_EXISTS", "Entity already exists";
+    NoEntity = 6, "ERR_NO_ENTITY", "Entity doesn't exist";
+    InviteExists = 7, "ERR_INVITE_EXISTS", "Invite already exists";
+    NoInvite = 8, "ERR_NO_INVITE", "Invite doesn't exist";
+    NoRequest = 9, "ERR_NO_REQUEST", "Contribution request doesn't exist";
+    NoContribution = 10, "ERR_NO_CONTRIBUTION", "Contribution doesn't exist";
+    NoContributionNeed = 11, "ERR_NO_CONTRIBUTION_NEED", "Contribution need doesn't exist";
+    CannotGrantOwner = 12, "ERR_CANNOT_GRANT_OWNER", "Ownership can only be transferred";
+    CannotRevokeOwner = 13, "ERR_CANNOT_REVOKE_OWNER", "Ownership can only be transferred";
+    NoPermissionToRevoke = 14, "ERR_NO_PERMISSION_TO_REVOKE", "Contributor doesn't have this permission";
+    LastAdmin = 15, "ERR_LAST_ADMIN", "Entity must keep at least one admin";
+    OnlyOwner = 16, "ERR_ONLY_OWNER", "Only the entity owner can call this method";
+    InsufficientStorage = 17, "ERR_INSUFFICIENT_STORAGE", "Storage deposit doesn't cover the state used";
+    DepositLessThanMinStorage = 18, "ERR_DEPOSIT_LESS_THAN_MIN_STORAGE", "Deposit is less than the minimum storage balance";
+    NotRegisteredForStorage = 19, "ERR_NOT_REGISTERED_FOR_STORAGE", "Account has no storage deposit";
+    WithdrawMoreThanAvailable = 20, "ERR_WITHDRAW_MORE_THAN_AVAILABLE", "Amount is more than the available storage balance";
+    StorageInUse = 21, "ERR_STORAGE_IN_USE", "Account still uses contract state";
+    ForceUnregisterNotSupported = 22, "ERR_FORCE_UNREGISTER_NOT_SUPPORTED", "Force unregistration is not supported";
+    EmptyName = 23, "ERR_EMPTY_NAME", "Name can't be empty";
+    NameTooLong = 24, "ERR_NAME_TOO_LONG", "Name is too long";
+    InvalidNameCharacters = 25, "ERR_INVALID_NAME_CHARACTERS", "Name contains control characters";
+    DescriptionTooLong = 26, "ERR_DESCRIPTION_TOO_LONG", "Description is too long";
+    InvalidDescriptionCharacters = 27, "ERR_INVALID_DESCRIPTION_CHARACTERS", "Description contains control characters";
+    ContributionTypeTooLong = 28, "ERR_CONTRIBUTION_TYPE_TOO_LONG", "Contribution type is too long";
+    InvalidContributionTypeCharacters = 29, "ERR_INVALID_CONTRIBUTION_TYPE_CHARACTERS", "Contribution type contains control characters";
+    TooManyContributionTypes = 30, "ERR_TOO_MANY_CONTRIBUTION_TYPES", "Too many contribution types";
+    TooManySkills = 31, "ERR_TOO_MANY_SKILLS", "Too many skills";
+    EmptySkill = 32, "ERR_EMPTY_SKILL", "Skill can't be empty";
+    SkillTooLong = 33, "ERR_SKILL_TOO_LONG", "Skill is too long";
+    InvalidSkillCharacters = 34, "ERR_INVALID_SKILL_CHARACTERS", "Skill contains control characters";
+    ResumeTooLong = 35, "ERR_RESUME_TOO_LONG", "Resume is too long";
+    InvalidResumeCharacters = 36, "ERR_INVALID_RESUME_CHARACTERS", "Resume contains control characters";
+    EntityNotActive = 37, "ERR_ENTITY_NOT_ACTIVE", "Entity is closed or flagged";
+    EntityNotClosed = 38, "ERR_ENTITY_NOT_CLOSED", "Entity is not closed";
+    EntityNotFlagged = 39, "ERR_ENTITY_NOT_FLAGGED", "Entity is not flagged";
+    EntityFlagged = 40, "ERR_ENTITY_FLAGGED", "Entity is flagged for review";
+    EntityRemoving = 41, "ERR_ENTITY_REMOVING", "Entity is being removed";
+    ContractPaused = 42, "ERR_CONTRACT_PAUSED", "Contract is paused by the moderator";
+    CategoryPaused = 43, "ERR_CATEGORY_PAUSED", "Methods of this category are paused by the moderator";
+    NotModerator = 44, "ERR_NOT_MODERATOR", "Account is not a moderator";
+    ModeratorExists = 45, "ERR_MODERATOR_EXISTS", "Account is already a moderator";
+    InvalidThreshold = 46, "ERR_INVALID_THRESHOLD", "Threshold must be between one and the number of moderators";
+    LastModerator = 47, "ERR_LAST_MODERATOR", "Council must keep at least one moderator";
+    NoStagedUpgrade = 48, "ERR_NO_STAGED_UPGRADE", "There is no staged upgrade";
+    UpgradeNotReady = 49, "ERR_UPGRADE_NOT_READY", "Upgrade delay hasn't passed yet";
+    UpgradeHashMismatch = 50, "ERR_UPGRADE_HASH_MISMATCH", "Staged code doesn't match the given hash";
+    UnknownStateVersion = 51, "ERR_UNKNOWN_STATE_VERSION", "Stored state version is not supported by this code";
+    MigrationInProgress = 52, "ERR_MIGRATION_IN_PROGRESS", "State migration is in progress";
+    NeedAlreadyProposed = 53, "ERR_NEED_ALREADY_PROPOSED", "Contributor already requested to contribute to this need";
+    OwnerCannotLeave = 54, "ERR_OWNER_CANNOT_LEAVE", "Owners have to transfer the ownership before leaving";
+    InvalidDateRange = 55, "ERR_INVALID_DATE_RANGE", "Start date must not be after the end date";
+    ContributionVerified = 56, "ERR_CONTRIBUTION_VERIFIED", "Contribution is already verified";
+    CannotEndorseSelf = 57, "ERR_CANNOT_ENDORSE_SELF", "Accounts can't endorse themselves";
+    NoSkill = 58, "ERR_NO_SKILL", "Contributor doesn't have this skill";
+    EndorsementExists = 59, "ERR_ENDORSEMENT_EXISTS", "Account already endorsed this";
+    NoEndorsement = 60, "ERR_NO_ENDORSEMENT", "Endorsement doesn't exist";
+    InvalidRating = 61, "ERR_INVALID_RATING", "Rating must be between one and five";
+    ContributionNotFinished = 62, "ERR_CONTRIBUTION_NOT_FINISHED", "Contribution has no end date yet";
+    ReviewLocked = 63, "ERR_REVIEW_LOCKED", "Review can no longer be edited";
+    ZeroDeposit = 64, "ERR_ZERO_DEPOSIT", "Attach a deposit to fund the escrow";
+    NeedNotActive = 65, "ERR_NEED_NOT_ACTIVE", "Contribution need isn't active";
+    TokenNotWhitelisted = 66, "ERR_TOKEN_NOT_WHITELISTED", "Token isn't accepted for payments";
+    InvalidEscrow = 67, "ERR_INVALID_ESCROW", "Transfer message must be an escrow to fund";
+    ContributionFinished = 68, "ERR_CONTRIBUTION_FINISHED", "Contribution already has an end date";
+    NoEscrow = 69, "ERR_NO_ESCROW", "Nothing is held in escrow";
+    NoMilestone = 70, "ERR_NO_MILESTONE", "Milestone doesn't exist";
+    InvalidMilestoneStatus = 71, "ERR_INVALID_MILESTONE_STATUS", "Milestone can't move to this status";
+    InsufficientEscrow = 72, "ERR_INSUFFICIENT_ESCROW", "Not enough of the token is held in escrow";
+    InvalidPayment = 73, "ERR_INVALID_PAYMENT", "Payment amount must be positive";
+    ContributionNotVerified = 74, "ERR_CONTRIBUTION_NOT_VERIFIED", "Contribution isn't verified by the entity";
+    NothingToWithdraw = 75, "ERR_NOTHING_TO_WITHDRAW", "Nothing of the token can be withdrawn";
+}
+
+impl ContractError {
+    /// Abort execution with this error. Like `require!`, panics natively outside of wasm, as
+    /// unit tests can't unwind through the mocked host.
+    pub fn panic(&self) -> ! {
+        if cfg!(target_arch = "wasm32") {
+            env::panic_str(&self.to_string())
+        } else {
+            panic!("{}", self)
+        }
+    }
+}
+
+/// Panic messages are formatted as `ERR_NAME (code): message`.
+impl fmt::Display for ContractError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({}): {}", self.name(), self.code(), self.message())
+    }
+}
+
+/// Entry of the error catalogue.
+#[derive(Deserialize, Serialize, JsonSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ErrorInfo {
+    pub code: u32,
+    pub name: String,
+    pub message: String,
+}
+
+impl From<&ContractError> for ErrorInfo {
+    fn from(value: &ContractError) -> Self {
+        Self {
+            code: value.code(),
+            name: value.name().to_string(),
+            message: value.message().to_string(),
+        }
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    // Views
+
+    /// Get all the errors the contract can fail with.
+    pub fn get_errors(&self) -> Vec<ErrorInfo> {
+        ContractError::ALL.iter().map(Into::into).collect()
+    }
+}
@@ -1,17 +1,160 @@
+use near_sdk::json_types::Base58CryptoHash;
 use near_sdk::serde::{Deserialize, Serialize};
-use near_sdk::{env, serde_json, AccountId, Timestamp};
+use near_sdk::serde_json::{self, Value};
+use near_sdk::{env, AccountId, Balance, Timestamp};
+use std::collections::HashSet;
 
-use crate::contributor::ContributionType;
-use crate::dec_serde::u64_dec_format;
+use crate::bounty::Escrow;
+use crate::contribution::ContributionNeed;
+use crate::contributor::{ContributionType, Contributor};
+use crate::council::ModeratorAction;
+use crate::dec_serde::{u128_dec_format, u64_dec_format};
+use crate::endorsement::EndorsementTarget;
+use crate::entity::{Entity, EntityKind, Permission};
+use crate::migration::BatchMigration;
+use crate::milestone::Milestone;
+use crate::pause::PauseCategory;
+use crate::review::{Review, ReviewSide};
+use crate::token::Token;
+use crate::validation::ValidationConfig;
 
 #[derive(Deserialize, Serialize)]
-#[serde(crate = "near_sdk::serde")]
+#[serde(
+    crate = "near_sdk::serde",
+    tag = "event",
+    content = "data",
+    rename_all = "snake_case"
+)]
 pub enum Events {
+    SetModerator {
+        old_moderator_ids: Vec<AccountId>,
+        new_moderator_id: AccountId,
+    },
+    AddModerator {
+        moderator_id: AccountId,
+    },
+    RemoveModerator {
+        moderator_id: AccountId,
+    },
+    SetModeratorThreshold {
+        old: Option<u32>,
+        new: Option<u32>,
+    },
+    ApproveModeratorAction {
+        proposal_id: Base58CryptoHash,
+        action: ModeratorAction,
+        moderator_id: AccountId,
+        approvals: u32,
+        threshold: u32,
+    },
+    SetValidationConfig {
+        old: ValidationConfig,
+        new: ValidationConfig,
+    },
+    StageUpgrade {
+        code_hash: Base58CryptoHash,
+        #[serde(with = "u64_dec_format")]
+        deploy_after: Timestamp,
+    },
+    CancelUpgrade {
+        code_hash: Base58CryptoHash,
+    },
+    SetUpgradeDelay {
+        #[serde(with = "u64_dec_format")]
+        old: Timestamp,
+        #[serde(with = "u64_dec_format")]
+        new: Timestamp,
+    },
+    Upgrade {
+        code_hash: Base58CryptoHash,
+    },
+    Migrate {
+        version: String,
+        state_version: u32,
+    },
+    MigrateState {
+        migrated: u32,
+        pending: Option<BatchMigration>,
+    },
+    Pause {
+        categories: Option<HashSet<PauseCategory>>,
+    },
+    Unpause {
+        categories: Option<HashSet<PauseCategory>>,
+    },
+    StorageDeposit {
+        account_id: AccountId,
+        #[serde(with = "u128_dec_format")]
+        amount: Balance,
+    },
+    StorageWithdraw {
+        account_id: AccountId,
+        #[serde(with = "u128_dec_format")]
+        amount: Balance,
+    },
+    StorageUnregister {
+        account_id: AccountId,
+        #[serde(with = "u128_dec_format")]
+        amount: Balance,
+    },
     AddEntity {
         entity_id: AccountId,
+        founder_id: AccountId,
+        name: String,
+        kind: EntityKind,
+        #[serde(with = "u64_dec_format")]
+        start_date: Timestamp,
+    },
+    CloseEntity {
+        entity_id: AccountId,
+        #[serde(with = "u64_dec_format")]
+        end_date: Timestamp,
+    },
+    ReactivateEntity {
+        entity_id: AccountId,
+    },
+    FlagEntity {
+        entity_id: AccountId,
+        reason: String,
+    },
+    UnflagEntity {
+        entity_id: AccountId,
+        reason: String,
+    },
+    RemoveEntity {
+        entity_id: AccountId,
+    },
+    RemoveContributionNeed {
+        entity_id: AccountId,
+        cid: String,
+    },
+    RemoveInvite {
+        entity_id: AccountId,
+        contributor_id: AccountId,
+    },
+    RemoveContributionRequest {
+        #[serde(with = "u64_dec_format")]
+        request_id: u64,
+        entity_id: AccountId,
+        contributor_id: AccountId,
+    },
+    RemoveContribution {
+        entity_id: AccountId,
+        contributor_id: AccountId,
+    },
+    SetEntity {
+        entity_id: AccountId,
+        old: Option<Entity>,
+        new: Entity,
     },
     RegisterContributor {
         contributor_id: AccountId,
+        contributor: Contributor,
+    },
+    EditContributor {
+        contributor_id: AccountId,
+        old: Option<Contributor>,
+        new: Contributor,
     },
     PostContributionNeed {
         entity_id: AccountId,
@@ -19,30 +162,161 @@ pub enum Events {
         description: String,
         contribution_type: ContributionType,
     },
+    FundEscrow {
+        escrow: Escrow,
+        account_id: AccountId,
+        token: Token,
+        #[serde(with = "u128_dec_format")]
+        amount: Balance,
+    },
+    ReleaseEscrow {
+        escrow: Escrow,
+        contributor_id: AccountId,
+        contribution_id: u32,
+        token: Token,
+        #[serde(with = "u128_dec_format")]
+        amount: Balance,
+    },
+    RefundEscrow {
+        escrow: Escrow,
+        account_id: AccountId,
+        token: Token,
+        #[serde(with = "u128_dec_format")]
+        amount: Balance,
+    },
+    CompleteEscrowTransfer {
+        escrow: Escrow,
+        token: Token,
+        receiver_id: AccountId,
+        #[serde(with = "u128_dec_format")]
+        amount: Balance,
+    },
+    RevertEscrowTransfer {
+        escrow: Escrow,
+        token: Token,
+        receiver_id: AccountId,
+        #[serde(with = "u128_dec_format")]
+        amount: Balance,
+    },
+    AddClaimable {
+        escrow: Escrow,
+        account_id: AccountId,
+        token: Token,
+        #[serde(with = "u128_dec_format")]
+        amount: Balance,
+    },
+    Withdraw {
+        account_id: AccountId,
+        token: Token,
+        #[serde(with = "u128_dec_format")]
+        amount: Balance,
+    },
+    RevertWithdraw {
+        account_id: AccountId,
+        token: Token,
+        #[serde(with = "u128_dec_format")]
+        amount: Balance,
+    },
+    AddToken {
+        token_id: AccountId,
+    },
+    RemoveToken {
+        token_id: AccountId,
+    },
+    SetContributionNeed {
+        entity_id: AccountId,
+        cid: String,
+        old: ContributionNeed,
+        new: ContributionNeed,
+    },
     RequestContribution {
+        #[serde(with = "u64_dec_format")]
+        request_id: u64,
         entity_id: AccountId,
         contributor_id: AccountId,
         description: String,
         contribution_type: ContributionType,
+        need: Option<String>,
     },
     RejectContribution {
+        #[serde(with = "u64_dec_format")]
+        request_id: u64,
+        entity_id: AccountId,
+        contributor_id: AccountId,
+    },
+    WithdrawContributionRequest {
+        #[serde(with = "u64_dec_format")]
+        request_id: u64,
         entity_id: AccountId,
         contributor_id: AccountId,
     },
     ApproveContribution {
+        #[serde(with = "u64_dec_format")]
+        request_id: u64,
+        contribution_id: u32,
         entity_id: AccountId,
         contributor_id: AccountId,
         description: String,
         contribution_type: ContributionType,
+        need: Option<String>,
         #[serde(with = "u64_dec_format")]
         start_date: Timestamp,
     },
     FinishContribution {
         entity_id: AccountId,
         contributor_id: AccountId,
+        contribution_id: u32,
         #[serde(with = "u64_dec_format")]
         end_date: Timestamp,
     },
+    ClaimContribution {
+        entity_id: AccountId,
+        contributor_id: AccountId,
+        contribution_id: u32,
+        description: String,
+        contribution_type: ContributionType,
+        #[serde(with = "u64_dec_format")]
+        start_date: Timestamp,
+        #[serde(with = "u64_dec_format")]
+        end_date: Timestamp,
+    },
+    AddMilestone {
+        entity_id: AccountId,
+        contributor_id: AccountId,
+        contribution_id: u32,
+        milestone_id: u32,
+        milestone: Milestone,
+    },
+    SubmitMilestone {
+        entity_id: AccountId,
+        contributor_id: AccountId,
+        contribution_id: u32,
+        milestone_id: u32,
+    },
+    ApproveMilestone {
+        entity_id: AccountId,
+        contributor_id: AccountId,
+        contribution_id: u32,
+        milestone_id: u32,
+    },
+    RejectMilestone {
+        entity_id: AccountId,
+        contributor_id: AccountId,
+        contribution_id: u32,
+        milestone_id: u32,
+        reason: String,
+    },
+    VerifyContribution {
+        entity_id: AccountId,
+        contributor_id: AccountId,
+        contribution_id: u32,
+    },
+    DisputeContribution {
+        entity_id: AccountId,
+        contributor_id: AccountId,
+        contribution_id: u32,
+        reason: String,
+    },
     InviteContributor {
         entity_id: AccountId,
         contributor_id: AccountId,
@@ -50,22 +324,675 @@ pub enum Events {
         contribution_type: ContributionType,
         #[serde(with = "u64_dec_format")]
         start_date: Timestamp,
+        permissions: HashSet<Permission>,
     },
     AcceptInvite {
         entity_id: AccountId,
         contributor_id: AccountId,
+        contribution_id: u32,
         description: String,
         contribution_type: ContributionType,
         #[serde(with = "u64_dec_format")]
         start_date: Timestamp,
     },
+    RejectInvite {
+        entity_id: AccountId,
+        contributor_id: AccountId,
+    },
+    CancelInvite {
+        entity_id: AccountId,
+        contributor_id: AccountId,
+    },
+    LeaveEntity {
+        entity_id: AccountId,
+        contributor_id: AccountId,
+        #[serde(with = "u64_dec_format")]
+        end_date: Timestamp,
+    },
+    ReviewContribution {
+        entity_id: AccountId,
+        contributor_id: AccountId,
+        contribution_id: u32,
+        side: ReviewSide,
+        old: Option<Review>,
+        new: Review,
+    },
+    RemoveReview {
+        entity_id: AccountId,
+        contributor_id: AccountId,
+        contribution_id: u32,
+        side: ReviewSide,
+    },
+    Endorse {
+        target: EndorsementTarget,
+        endorser_id: AccountId,
+        text: Option<String>,
+    },
+    RevokeEndorsement {
+        target: EndorsementTarget,
+        endorser_id: AccountId,
+    },
+    RemoveEndorsement {
+        target: EndorsementTarget,
+        endorser_id: AccountId,
+    },
+    GrantPermission {
+        entity_id: AccountId,
+        contributor_id: AccountId,
+        permission: Permission,
+    },
+    RevokePermission {
+        entity_id: AccountId,
+        contributor_id: AccountId,
+        permission: Permission,
+    },
+    TransferOwnership {
+        entity_id: AccountId,
+        owner_id: AccountId,
+    },
+}
+
+/// Name of the NEP-297 standard the events are emitted under.
+pub const EVENT_STANDARD: &str = "nearcontribute";
+/// Version of the event standard. Bump when the shape of any event data changes.
+pub const EVENT_STANDARD_VERSION: &str = "1.6.0";
+
+/// NEP-297 event envelope.
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+struct EventLog {
+    standard: &'static str,
+    version: &'static str,
+    event: String,
+    data: Vec<Value>,
 }
 
 impl Events {
+    /// Splits the event into its snake_case name and data.
+    fn into_parts(self) -> (String, Value) {
+        let Value::Object(mut event) = serde_json::to_value(self).unwrap() else {
+            unreachable!();
+        };
+        let Some(Value::String(name)) = event.remove("event") else {
+            unreachable!();
+        };
+        (name, event.remove("data").unwrap_or(Value::Null))
+    }
+
     pub(crate) fn emit(self) {
-        env::log_str(&format!(
-            "EVENT_JSON:{}",
-            &serde_json::to_string(&self).unwrap()
-        ));
+        Self::emit_batch(vec![self]);
+    }
+
+    /// Emit the events. Consecutive events with the same name are batched into one log.
+    pub(crate) fn emit_batch(events: Vec<Events>) {
+        let mut logs: Vec<EventLog> = vec![];
+        for event in events {
+            let (name, data) = event.into_parts();
+            match logs.last_mut() {
+                Some(log) if log.event == name => log.data.push(data),
+                _ => logs.push(EventLog {
+                    standard: EVENT_STANDARD,
+                    version: EVENT_STANDARD_VERSION,
+                    event: name,
+                    data: vec![data],
+                }),
+            }
+        }
+        for log in logs {
+            env::log_str(&format!(
+                "EVENT_JSON:{}",
+                &serde_json::to_string(&log).unwrap()
+            ));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use near_contract_standards::fungible_token::receiver::FungibleTokenReceiver;
+    use near_contract_standards::storage_management::StorageManagement;
+    use near_sdk::json_types::{Base58CryptoHash, U128, U64};
+    use near_sdk::test_utils::VMContextBuilder;
+    use near_sdk::{
+        env, serde_json, testing_env, AccountId, PromiseResult, RuntimeFeesConfig, VMConfig,
+        ONE_NEAR, ONE_YOCTO,
+    };
+    use std::collections::HashSet;
+
+    use crate::bounty::Escrow;
+    use crate::contributor::ContributionType;
+    use crate::endorsement::EndorsementTarget;
+    use crate::entity::{EntityKind, Permission};
+    use crate::milestone::{Milestone, MilestoneStatus};
+    use crate::pause::PauseCategory;
+    use crate::test_utils::*;
+    use crate::token::{Payment, Token};
+    use crate::upgrade::stage_upgrade;
+    use crate::Contract;
+
+    fn token_id() -> AccountId {
+        "token.near".parse().unwrap()
+    }
+
+    /// Makes the next call come from the account with the raw input.
+    fn set_caller_with_input(predecessor_id: &AccountId, input: Vec<u8>) {
+        let mut context = VMContextBuilder::new()
+            .current_account_id(contract_id())
+            .predecessor_account_id(predecessor_id.clone())
+            .block_timestamp(NOW)
+            .build();
+        context.input = input;
+        testing_env!(context);
+    }
+
+    /// Makes the next calls come from the account without charging gas, so an upgrade can be
+    /// deployed with the prepaid gas of the mocked blockchain.
+    fn set_caller_without_fees(predecessor_id: &AccountId) {
+        testing_env!(
+            VMContextBuilder::new()
+                .current_account_id(contract_id())
+                .predecessor_account_id(predecessor_id.clone())
+                .block_timestamp(NOW)
+                .build(),
+            VMConfig::free(),
+            RuntimeFeesConfig::free(),
+        );
+    }
+
+    /// Makes the next call a callback of the contract with the promise result.
+    fn set_callback_result(result: PromiseResult) {
+        testing_env!(
+            VMContextBuilder::new()
+                .current_account_id(contract_id())
+                .predecessor_account_id(contract_id())
+                .block_timestamp(NOW)
+                .build(),
+            VMConfig::test(),
+            RuntimeFeesConfig::test(),
+            Default::default(),
+            vec![result],
+        );
+    }
+
+    /// Sources of the modules that define contract methods.
+    const SOURCES: &[(&str, &str)] = &[
+        ("bounty.rs", include_str!("bounty.rs")),
+        ("contribution.rs", include_str!("contribution.rs")),
+        ("contributor.rs", include_str!("contributor.rs")),
+        ("council.rs", include_str!("council.rs")),
+        ("endorsement.rs", include_str!("endorsement.rs")),
+        ("entity.rs", include_str!("entity.rs")),
+        ("errors.rs", include_str!("errors.rs")),
+        ("lib.rs", include_str!("lib.rs")),
+        ("migration.rs", include_str!("migration.rs")),
+        ("milestone.rs", include_str!("milestone.rs")),
+        ("pause.rs", include_str!("pause.rs")),
+        ("review.rs", include_str!("review.rs")),
+        ("token.rs", include_str!("token.rs")),
+        ("storage.rs", include_str!("storage.rs")),
+        ("upgrade.rs", include_str!("upgrade.rs")),
+        ("validation.rs", include_str!("validation.rs")),
+    ];
+
+    /// Finds the `&mut self` contract methods of a source file, as name and source. Contract
+    /// methods are the public methods and trait methods of `#[near_bindgen]` impl blocks, and
+    /// the `#[no_mangle]` functions.
+    fn mutating_methods(source: &str) -> Vec<(String, String)> {
+        let lines: Vec<&str> = source.lines().collect();
+        let mut methods = vec![];
+        let mut in_bindgen_impl = false;
+        let mut in_trait_impl = false;
+        let mut i = 0;
+        while i < lines.len() {
+            let line = lines[i];
+            if line.starts_with("impl ") {
+                in_bindgen_impl = i > 0 && lines[i - 1] == "#[near_bindgen]";
+                in_trait_impl = line.contains(" for Contract");
+            }
+            // Exported functions that load and write the state themselves.
+            if let Some(signature) = line
+                .strip_prefix("pub fn ")
+                .filter(|_| i > 0 && lines[i - 1] == "#[no_mangle]")
+            {
+                let name = signature.split('(').next().unwrap().to_string();
+                let end = i + lines[i..].iter().position(|line| *line == "}").unwrap();
+                methods.push((name, lines[i..end].join("\n")));
+                i = end;
+                continue;
+            }
+            let signature = line.strip_prefix("    pub fn ").or_else(|| {
+                in_trait_impl
+                    .then(|| line.strip_prefix("    fn "))
+                    .flatten()
+            });
+            let Some(signature) = signature.filter(|_| in_bindgen_impl) else {
+                i += 1;
+                continue;
+            };
+            let name = signature.split('(').next().unwrap().to_string();
+            let end = i + lines[i..].iter().position(|line| *line == "    }").unwrap();
+            let method = lines[i..end].join("\n");
+            let takes_mut_self = method
+                .split_once('(')
+                .map(|(_, params)| params.trim_start().starts_with("&mut self"))
+                .unwrap_or_default();
+            if takes_mut_self {
+                methods.push((name, method));
+            }
+            i = end;
+        }
+        methods
+    }
+
+    /// Every state changing contract method, as file and name with its source.
+    fn all_mutating_methods() -> Vec<(String, String)> {
+        SOURCES
+            .iter()
+            .flat_map(|(file, source)| {
+                mutating_methods(source)
+                    .into_iter()
+                    .map(move |(name, method)| (format!("{file}: {name}"), method))
+            })
+            .collect()
+    }
+
+    /// Whether a test below calls the method and asserts on the events in the next statement.
+    fn asserts_events(name: &str) -> bool {
+        let tests = include_str!("events.rs")
+            .split_once("\nmod tests {")
+            .unwrap()
+            .1;
+        tests.match_indices(&format!("{name}(")).any(|(at, _)| {
+            // Calls as a method or as a function, not calls of methods ending with the name.
+            if !tests[..at].ends_with(['.', ' ']) {
+                return false;
+            }
+            let Some((_, rest)) = tests[at..].split_once(";\n") else {
+                return false;
+            };
+            let next = rest.trim_start();
+            let next = &next[..next.find(";\n").unwrap_or(next.len())];
+            next.starts_with("assert")
+                && (next.contains("event_names()") || next.contains("events()"))
+        })
+    }
+
+    #[test]
+    fn every_mutating_method_emits_an_event() {
+        let missing: Vec<String> = all_mutating_methods()
+            .into_iter()
+            .filter(|(_, method)| !method.contains(".emit()") && !method.contains("emit_batch("))
+            .map(|(name, _)| name)
+            .collect();
+        assert!(missing.is_empty(), "methods without events: {missing:?}");
+    }
+
+    #[test]
+    fn every_mutating_method_is_tested_for_its_events() {
+        let missing: Vec<String> = all_mutating_methods()
+            .into_iter()
+            .map(|(name, _)| name)
+            .filter(|name| !asserts_events(name.rsplit(' ').next().unwrap()))
+            .collect();
+        assert!(
+            missing.is_empty(),
+            "methods without event tests: {missing:?}"
+        );
+    }
+
+    #[test]
+    fn moderator_methods_emit_events() {
+        let mut contract = setup();
+        contract.add_moderator(alice());
+        assert_eq!(event_names(), ["approve_moderator_action", "add_moderator"]);
+        set_caller(&moderator());
+        contract.set_moderator_threshold(Some(1));
+        assert_eq!(
+            event_names(),
+            ["approve_moderator_action", "set_moderator_threshold"]
+        );
+        set_caller(&moderator());
+        contract.remove_moderator(alice());
+        assert_eq!(
+            event_names(),
+            ["approve_moderator_action", "remove_moderator"]
+        );
+        set_caller(&moderator());
+        contract.set_moderator(bob(), Some(moderator()));
+        assert_eq!(event_names(), ["approve_moderator_action", "set_moderator"]);
+        set_caller(&bob());
+        contract.pause(Some(HashSet::from([PauseCategory::Reviews])));
+        assert_eq!(event_names(), ["pause"]);
+        set_caller(&bob());
+        contract.unpause(None);
+        assert_eq!(event_names(), ["unpause"]);
+        set_caller(&bob());
+        contract.add_token(alice());
+        assert_eq!(event_names(), ["add_token"]);
+        set_caller(&bob());
+        contract.remove_token(alice());
+        assert_eq!(event_names(), ["remove_token"]);
+        set_caller(&bob());
+        contract.set_validation_config(contract.get_validation_config());
+        assert_eq!(event_names(), ["set_validation_config"]);
+        set_caller(&bob());
+        assert!(contract.migrate_state(None));
+        assert_eq!(event_names(), ["migrate_state"]);
+    }
+
+    #[test]
+    fn entity_methods_emit_events() {
+        let mut contract = setup();
+        deposit_storage(&mut contract, &owner());
+        set_caller(&owner());
+        contract.add_entity(
+            entity(),
+            "Entity".to_string(),
+            EntityKind::Project,
+            U64(NOW),
+        );
+        assert_eq!(event_names(), ["add_entity"]);
+        set_caller(&moderator());
+        contract.admin_add_entity(
+            bob(),
+            alice(),
+            "Other".to_string(),
+            EntityKind::Project,
+            U64(NOW),
+        );
+        assert_eq!(event_names(), ["approve_moderator_action", "add_entity"]);
+        set_caller(&owner());
+        contract.set_entity(entity(), contract.get_entity(entity()));
+        assert_eq!(event_names(), ["set_entity"]);
+        set_caller(&owner());
+        contract.close_entity(entity(), U64(NOW));
+        assert_eq!(event_names(), ["close_entity"]);
+        set_caller(&owner());
+        contract.reactivate_entity(entity());
+        assert_eq!(event_names(), ["reactivate_entity"]);
+        set_caller(&moderator());
+        contract.flag_entity(entity(), "Spam".to_string());
+        assert_eq!(event_names(), ["flag_entity"]);
+        set_caller(&moderator());
+        contract.unflag_entity(entity(), "Not spam".to_string());
+        assert_eq!(event_names(), ["unflag_entity"]);
+        set_caller(&owner());
+        assert!(contract.remove_entity(entity(), None));
+        assert_eq!(event_names(), ["remove_contribution", "remove_entity"]);
+    }
+
+    #[test]
+    fn contribution_methods_emit_events() {
+        let mut contract = setup_with_entity();
+        set_caller(&owner());
+        contract.post_contribution_need(
+            entity(),
+            "Need".to_string(),
+            ContributionType::Development,
+        );
+        assert_eq!(event_names(), ["post_contribution_need"]);
+        let cid = contract.get_entity_contribution_needs(entity(), None, None)[0].clone();
+        set_caller(&owner());
+        let mut need = contract
+            .get_contribution_need(entity(), cid.clone())
+            .unwrap();
+        need.active = false;
+        contract.set_contribution_need(entity(), cid, need);
+        assert_eq!(event_names(), ["set_contribution_need"]);
+
+        deposit_storage(&mut contract, &alice());
+        set_caller(&alice());
+        let request_id = contract.request_contribution(
+            entity(),
+            "Request".to_string(),
+            ContributionType::Development,
+            None,
+        );
+        assert_eq!(event_names(), ["request_contribution"]);
+        set_caller(&alice());
+        contract.withdraw_contribution_request(request_id);
+        assert_eq!(event_names(), ["withdraw_contribution_request"]);
+        set_caller(&alice());
+        let request_id = contract.request_contribution(
+            entity(),
+            "Request".to_string(),
+            ContributionType::Development,
+            None,
+        );
+        set_caller(&owner());
+        contract.reject_contribution(request_id);
+        assert_eq!(event_names(), ["reject_contribution"]);
+
+        set_caller(&alice());
+        let request_id = contract.request_contribution(
+            entity(),
+            "Request".to_string(),
+            ContributionType::Development,
+            None,
+        );
+        set_caller(&owner());
+        contract.approve_contribution(request_id, None, None, None);
+        assert_eq!(event_names(), ["approve_contribution"]);
+        let contribution_id = 0;
+        set_caller(&owner());
+        contract.finish_contribution(entity(), alice(), contribution_id, U64(NOW));
+        assert_eq!(event_names(), ["finish_contribution"]);
+
+        set_caller(&alice());
+        let contribution_id = contract.claim_contribution(
+            entity(),
+            "Claim".to_string(),
+            ContributionType::Development,
+            U64(0),
+            U64(NOW),
+        );
+        assert_eq!(event_names(), ["claim_contribution"]);
+        set_caller(&owner());
+        contract.dispute_contribution(entity(), alice(), contribution_id, "Unknown".to_string());
+        assert_eq!(event_names(), ["dispute_contribution"]);
+        set_caller(&owner());
+        contract.verify_contribution(entity(), alice(), contribution_id);
+        assert_eq!(event_names(), ["verify_contribution"]);
+    }
+
+    #[test]
+    fn invite_and_permission_methods_emit_events() {
+        let mut contract = setup_with_entity();
+        let invite = |contract: &mut crate::Contract, permissions: HashSet<Permission>| {
+            set_caller(&owner());
+            contract.invite_contributor(
+                entity(),
+                alice(),
+                "Invite".to_string(),
+                ContributionType::Development,
+                U64(NOW),
+                permissions,
+            );
+        };
+        set_caller(&owner());
+        contract.invite_contributor(
+            entity(),
+            alice(),
+            "Invite".to_string(),
+            ContributionType::Development,
+            U64(NOW),
+            HashSet::new(),
+        );
+        assert_eq!(event_names(), ["invite_contributor"]);
+        set_caller(&owner());
+        contract.cancel_invite(entity(), alice());
+        assert_eq!(event_names(), ["cancel_invite"]);
+        invite(&mut contract, HashSet::new());
+        set_caller(&alice());
+        contract.reject_invite(entity());
+        assert_eq!(event_names(), ["reject_invite"]);
+        invite(&mut contract, HashSet::from([Permission::Manager]));
+        deposit_storage(&mut contract, &alice());
+        set_caller(&alice());
+        contract.accept_invite(entity());
+        assert_eq!(event_names(), ["accept_invite"]);
+
+        set_caller(&owner());
+        contract.grant_permission(entity(), alice(), Permission::Admin);
+        assert_eq!(event_names(), ["grant_permission"]);
+        set_caller(&owner());
+        contract.revoke_permission(entity(), alice(), Permission::Admin);
+        assert_eq!(event_names(), ["revoke_permission"]);
+        set_caller(&owner());
+        contract.transfer_ownership(entity(), alice());
+        assert_eq!(event_names(), ["transfer_ownership"]);
+        set_caller(&owner());
+        contract.leave_entity(entity(), U64(NOW));
+        assert_eq!(event_names(), ["leave_entity"]);
+    }
+
+    #[test]
+    fn contributor_methods_emit_events() {
+        let mut contract = setup_with_entity();
+        let contribution_id = add_contribution(&mut contract, &alice());
+        set_caller_with_deposit(&alice(), ONE_YOCTO);
+        contract.register(
+            HashSet::from([ContributionType::Development]),
+            HashSet::from(["rust".to_string()]),
+            "Resume".to_string(),
+        );
+        assert_eq!(event_names(), ["register_contributor"]);
+        set_caller(&alice());
+        let contributor = contract.get_contributor(alice()).unwrap();
+        contract.edit_contributor(contributor);
+        assert_eq!(event_names(), ["edit_contributor"]);
+
+        let target = EndorsementTarget::Skill {
+            contributor_id: alice(),
+            skill: "rust".to_string(),
+        };
+        deposit_storage(&mut contract, &bob());
+        set_caller(&bob());
+        contract.endorse(target.clone(), None);
+        assert_eq!(event_names(), ["endorse"]);
+        set_caller(&bob());
+        contract.revoke_endorsement(target);
+        assert_eq!(event_names(), ["revoke_endorsement"]);
+
+        set_caller(&owner());
+        contract.finish_contribution(entity(), alice(), contribution_id, U64(NOW));
+        set_caller(&alice());
+        contract.review_contribution(entity(), alice(), contribution_id, 5, "Great".to_string());
+        assert_eq!(event_names(), ["review_contribution"]);
+    }
+
+    #[test]
+    fn storage_methods_emit_events() {
+        let mut contract = setup();
+        set_caller_with_deposit(&alice(), ONE_NEAR);
+        contract.storage_deposit(None, None);
+        assert_eq!(event_names(), ["storage_deposit"]);
+        set_caller_with_deposit(&alice(), ONE_YOCTO);
+        contract.storage_withdraw(Some(1.into()));
+        assert_eq!(event_names(), ["storage_withdraw"]);
+        set_caller_with_deposit(&alice(), ONE_YOCTO);
+        assert!(contract.storage_unregister(None));
+        assert_eq!(event_names(), ["storage_unregister"]);
+    }
+
+    #[test]
+    fn escrow_and_milestone_methods_emit_events() {
+        let mut contract = setup_with_entity();
+        let contribution_id = add_contribution(&mut contract, &alice());
+        let escrow = Escrow::Contribution {
+            entity_id: entity(),
+            contributor_id: alice(),
+            contribution_id,
+        };
+        set_caller_with_deposit(&owner(), ONE_NEAR);
+        contract.fund_escrow(escrow.clone());
+        assert_eq!(event_names(), ["fund_escrow"]);
+        set_caller(&owner());
+        contract.cancel_contribution_escrow(entity(), alice(), contribution_id);
+        assert_eq!(event_names(), ["refund_escrow"]);
+
+        set_caller(&moderator());
+        contract.add_token(token_id());
+        set_caller(&token_id());
+        contract.ft_on_transfer(owner(), U128(100), serde_json::to_string(&escrow).unwrap());
+        assert_eq!(event_names(), ["fund_escrow"]);
+        set_callback_result(PromiseResult::Successful(vec![]));
+        contract.on_escrow_transfer(
+            escrow.clone(),
+            Token::Near,
+            alice(),
+            vec![(owner(), U128(ONE_NEAR))],
+            Some(contribution_id),
+        );
+        assert_eq!(event_names(), ["complete_escrow_transfer"]);
+        set_callback_result(PromiseResult::Failed);
+        contract.on_escrow_transfer(
+            escrow.clone(),
+            Token::Near,
+            alice(),
+            vec![(bob(), U128(ONE_NEAR))],
+            Some(contribution_id),
+        );
+        assert_eq!(event_names(), ["revert_escrow_transfer", "add_claimable"]);
+        set_caller_with_deposit(&bob(), ONE_YOCTO);
+        contract.withdraw(Token::Near);
+        assert_eq!(event_names(), ["withdraw"]);
+        set_callback_result(PromiseResult::Failed);
+        contract.on_withdraw(bob(), Token::Near, U128(ONE_NEAR));
+        assert_eq!(event_names(), ["revert_withdraw"]);
+
+        let milestone = Milestone {
+            description: "Milestone".to_string(),
+            due_date: NOW,
+            payment: Some(Payment {
+                token: Token::Ft(token_id()),
+                amount: 100,
+            }),
+            status: MilestoneStatus::Pending,
+        };
+        set_caller(&owner());
+        contract.add_milestone(entity(), alice(), contribution_id, milestone);
+        assert_eq!(event_names(), ["add_milestone"]);
+        set_caller(&alice());
+        contract.submit_milestone(entity(), contribution_id, 0);
+        assert_eq!(event_names(), ["submit_milestone"]);
+        set_caller(&owner());
+        contract.reject_milestone(entity(), alice(), contribution_id, 0, "Late".to_string());
+        assert_eq!(event_names(), ["reject_milestone"]);
+        set_caller(&alice());
+        contract.submit_milestone(entity(), contribution_id, 0);
+        set_caller(&owner());
+        contract.approve_milestone(entity(), alice(), contribution_id, 0);
+        assert_eq!(event_names(), ["approve_milestone", "release_escrow"]);
+    }
+
+    #[test]
+    fn upgrade_methods_emit_events() {
+        let mut contract = setup();
+        contract.set_upgrade_delay(U64(0));
+        assert_eq!(
+            event_names(),
+            ["approve_moderator_action", "set_upgrade_delay"]
+        );
+        let code = b"code".to_vec();
+        let code_hash = Base58CryptoHash::from(env::sha256_array(&code));
+        env::state_write(&contract);
+        drop(contract);
+        set_caller_with_input(&moderator(), code.clone());
+        stage_upgrade();
+        assert_eq!(event_names(), ["approve_moderator_action", "stage_upgrade"]);
+        let mut contract: Contract = env::state_read().unwrap();
+        set_caller_without_fees(&moderator());
+        contract.deploy_upgrade(code_hash);
+        assert_eq!(event_names(), ["upgrade"]);
+
+        env::state_write(&contract);
+        drop(contract);
+        set_caller_with_input(&moderator(), code);
+        stage_upgrade();
+        let mut contract: Contract = env::state_read().unwrap();
+        set_caller(&moderator());
+        contract.cancel_upgrade();
+        assert_eq!(event_names(), ["cancel_upgrade"]);
     }
 }
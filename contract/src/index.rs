@@ -0,0 +1,262 @@
+use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::store::{LookupMap, UnorderedSet};
+use near_sdk::AccountId;
+
+use crate::endorsement::EndorsementTarget;
+use crate::entity::Permission;
+use crate::{Contract, StorageKeys};
+
+/// Secondary index from a key, an account unless given, to a set of related values.
+pub type Index<V, K = AccountId> = LookupMap<K, UnorderedSet<V>>;
+
+/// Adds the value to the set stored for the key, creating the set under the given prefix if
+/// this is the first value for the key.
+fn insert<K, V>(index: &mut Index<V, K>, key: &K, value: V, prefix: StorageKeys)
+where
+    K: BorshSerialize + Ord + Clone,
+    V: BorshSerialize + BorshDeserialize + Ord + Clone,
+{
+    let set = index
+        .entry(key.clone())
+        .or_insert_with(|| UnorderedSet::new(prefix));
+    set.insert(value);
+    // Nested sets are not flushed with the map, write them out so storage usage is up to date.
+    set.flush();
+}
+
+/// Removes the value from the set stored for the key, dropping the set once it is empty.
+pub(crate) fn remove<K, V>(index: &mut Index<V, K>, key: &K, value: &V)
+where
+    K: BorshSerialize + Ord + Clone,
+    V: BorshSerialize + BorshDeserialize + Ord + Clone,
+{
+    let Some(set) = index.get_mut(key) else {
+        return;
+    };
+    if set.len() == 1 && set.contains(value) {
+        // Removed values leave empty slots behind that only clearing drains, so the last value
+        // is cleared with them instead of being removed.
+        set.clear();
+        set.flush();
+        index.remove(key);
+    } else {
+        set.remove(value);
+        set.flush();
+    }
+}
+
+/// Returns a page of values stored for the key.
+pub fn page<K, V>(index: &Index<V, K>, key: &K, from_index: usize, limit: usize) -> Vec<V>
+where
+    K: BorshSerialize + Ord + Clone,
+    V: BorshSerialize + BorshDeserialize + Ord + Clone,
+{
+    index
+        .get(key)
+        .map(|set| set.iter().skip(from_index).take(limit).cloned().collect())
+        .unwrap_or_default()
+}
+
+/// Returns the number of values stored for the key.
+pub fn len<K, V>(index: &Index<V, K>, key: &K) -> u64
+where
+    K: BorshSerialize + Ord + Clone,
+    V: BorshSerialize + BorshDeserialize + Ord + Clone,
+{
+    index
+        .get(key)
+        .map(|set| set.len() as u64)
+        .unwrap_or_default()
+}
+
+impl Contract {
+    /// Record a contribution between entity and contributor in the indexes.
+    pub(crate) fn index_contribution(&mut self, entity_id: &AccountId, contributor_id: &AccountId) {
+        insert(
+            &mut self.entity_contributions,
+            entity_id,
+            contributor_id.clone(),
+            StorageKeys::EntityContributionsSet(entity_id.clone()),
+        );
+        insert(
+            &mut self.contributor_contributions,
+            contributor_id,
+            entity_id.clone(),
+            StorageKeys::ContributorContributionsSet(contributor_id.clone()),
+        );
+    }
+
+    /// Remove a contribution between entity and contributor from the indexes.
+    pub(crate) fn unindex_contribution(
+        &mut self,
+        entity_id: &AccountId,
+        contributor_id: &AccountId,
+    ) {
+        remove(&mut self.entity_contributions, entity_id, contributor_id);
+        remove(
+            &mut self.contributor_contributions,
+            contributor_id,
+            entity_id,
+        );
+        self.index_role(entity_id, contributor_id, None);
+    }
+
+    /// Record a contribution request from contributor to entity in the indexes.
+    pub(crate) fn index_request(
+        &mut self,
+        request_id: u64,
+        entity_id: &AccountId,
+        contributor_id: &AccountId,
+    ) {
+        insert(
+            &mut self.entity_requests,
+            entity_id,
+            request_id,
+            StorageKeys::EntityRequestIdsSet(entity_id.clone()),
+        );
+        insert(
+            &mut self.contributor_requests,
+            contributor_id,
+            request_id,
+            StorageKeys::ContributorRequestIdsSet(contributor_id.clone()),
+        );
+    }
+
+    /// Remove a contribution request from contributor to entity from the indexes.
+    pub(crate) fn unindex_request(
+        &mut self,
+        request_id: u64,
+        entity_id: &AccountId,
+        contributor_id: &AccountId,
+    ) {
+        remove(&mut self.entity_requests, entity_id, &request_id);
+        remove(&mut self.contributor_requests, contributor_id, &request_id);
+    }
+
+    /// Record an invite from entity to contributor in the indexes.
+    pub(crate) fn index_invite(&mut self, entity_id: &AccountId, contributor_id: &AccountId) {
+        insert(
+            &mut self.entity_invites,
+            entity_id,
+            contributor_id.clone(),
+            StorageKeys::EntityInvitesSet(entity_id.clone()),
+        );
+        insert(
+            &mut self.contributor_invites,
+            contributor_id,
+            entity_id.clone(),
+            StorageKeys::ContributorInvitesSet(contributor_id.clone()),
+        );
+    }
+
+    /// Remove an invite from entity to contributor from the indexes.
+    pub(crate) fn unindex_invite(&mut self, entity_id: &AccountId, contributor_id: &AccountId) {
+        remove(&mut self.entity_invites, entity_id, contributor_id);
+        remove(&mut self.contributor_invites, contributor_id, entity_id);
+    }
+
+    /// Record a need of the entity in the indexes.
+    pub(crate) fn index_need(&mut self, entity_id: &AccountId, cid: &str) {
+        insert(
+            &mut self.entity_needs,
+            entity_id,
+            cid.to_string(),
+            StorageKeys::EntityNeedsSet(entity_id.clone()),
+        );
+    }
+
+    /// Remove a need of the entity from the indexes.
+    pub(crate) fn unindex_need(&mut self, entity_id: &AccountId, cid: &str) {
+        remove(&mut self.entity_needs, entity_id, &cid.to_string());
+    }
+
+    /// Record an endorsement of the target by the endorser in the indexes.
+    pub(crate) fn index_endorsement(
+        &mut self,
+        target: &EndorsementTarget,
+        endorser_id: &AccountId,
+    ) {
+        insert(
+            &mut self.target_endorsements,
+            target,
+            endorser_id.clone(),
+            StorageKeys::TargetEndorsementsSet(target.clone()),
+        );
+        let contributor_id = target.contributor_id();
+        insert(
+            &mut self.contributor_endorsements,
+            contributor_id,
+            (target.clone(), endorser_id.clone()),
+            StorageKeys::ContributorEndorsementsSet(contributor_id.clone()),
+        );
+    }
+
+    /// Remove an endorsement of the target by the endorser from the indexes.
+    pub(crate) fn unindex_endorsement(
+        &mut self,
+        target: &EndorsementTarget,
+        endorser_id: &AccountId,
+    ) {
+        remove(&mut self.target_endorsements, target, endorser_id);
+        remove(
+            &mut self.contributor_endorsements,
+            target.contributor_id(),
+            &(target.clone(), endorser_id.clone()),
+        );
+    }
+
+    /// Record the role the account has in the entity in the indexes. Only managers (or higher) are
+    /// indexed.
+    pub(crate) fn index_role(
+        &mut self,
+        entity_id: &AccountId,
+        account_id: &AccountId,
+        role: Option<Permission>,
+    ) {
+        if role >= Some(Permission::Manager) {
+            insert(
+                &mut self.managed_entities,
+                account_id,
+                entity_id.clone(),
+                StorageKeys::ManagedEntitiesSet(account_id.clone()),
+            );
+        } else {
+            remove(&mut self.managed_entities, account_id, entity_id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use near_sdk::env;
+    use near_sdk::store::LookupMap;
+
+    use crate::index::{insert, remove, Index};
+    use crate::test_utils::*;
+    use crate::StorageKeys;
+
+    #[test]
+    fn removing_the_last_value_frees_the_set() {
+        set_caller(&moderator());
+        let mut index: Index<u64> = LookupMap::new(StorageKeys::EntityRequestIds);
+        let storage_usage = env::storage_usage();
+        insert(
+            &mut index,
+            &alice(),
+            1,
+            StorageKeys::EntityRequestIdsSet(alice()),
+        );
+        insert(
+            &mut index,
+            &alice(),
+            2,
+            StorageKeys::EntityRequestIdsSet(alice()),
+        );
+        index.flush();
+        assert!(env::storage_usage() > storage_usage);
+        remove(&mut index, &alice(), &1);
+        remove(&mut index, &alice(), &2);
+        index.flush();
+        assert_eq!(env::storage_usage(), storage_usage);
+    }
+}
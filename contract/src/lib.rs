@@ -1,97 +1,290 @@
 use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
-use near_sdk::store::UnorderedMap;
-use near_sdk::{env, near_bindgen, require, sys, AccountId, BorshStorageKey, Gas, PanicOnDefault};
+use near_sdk::json_types::U64;
+use near_sdk::store::{LazyOption, LookupMap, UnorderedMap, UnorderedSet};
+use near_sdk::{
+    env, near_bindgen, require, AccountId, Balance, BorshStorageKey, CryptoHash, PanicOnDefault,
+    Timestamp,
+};
 
+use crate::bounty::VersionedBounty;
 use crate::contribution::{
-    Contribution, VersionedContribution, VersionedContributionInvite, VersionedContributionNeed,
+    VersionedContribution, VersionedContributionInvite, VersionedContributionNeed,
     VersionedContributionRequest,
 };
 use crate::contributor::VersionedContributor;
-use crate::entity::{Permission, VersionedEntity};
+use crate::council::VersionedModeratorProposal;
+use crate::endorsement::{EndorsementTarget, VersionedEndorsement};
+use crate::entity::{Capability, Permission, VersionedEntity};
+use crate::errors::ContractError;
+use crate::index::Index;
+use crate::migration::{write_state_version, BatchMigration, LegacyRequests};
+use crate::pause::PauseState;
+use crate::review::{Rating, ReviewSide, VersionedReview};
+use crate::storage::{StoragePayers, StorageRecord, VersionedStorageAccount};
+use crate::token::Token;
+use crate::upgrade::{StagedUpgrade, DEFAULT_UPGRADE_DELAY};
+use crate::validation::ValidationConfig;
 
+mod bounty;
 mod contribution;
 mod contributor;
+mod council;
 mod dec_serde;
+mod endorsement;
 mod entity;
+mod errors;
 mod events;
+mod index;
+mod migration;
+mod milestone;
+mod pause;
+mod review;
+mod storage;
+#[cfg(test)]
+mod test_utils;
+mod token;
+mod upgrade;
+mod validation;
+
+/// Number of items returned by list views when no `limit` is given.
+const DEFAULT_PAGE_LIMIT: u64 = 100;
 
-const MAX_DESCRIPTION_LENGTH: usize = 420;
+/// Converts optional `from_index` and `limit` view arguments into the number of items to skip and
+/// the number of items to take. Values that don't fit in a `usize`, as on wasm32, are clamped.
+pub(crate) fn pagination(from_index: Option<U64>, limit: Option<U64>) -> (usize, usize) {
+    let from_index = from_index.map(u64::from).unwrap_or_default();
+    let limit = limit.map(u64::from).unwrap_or(DEFAULT_PAGE_LIMIT);
+    (
+        usize::try_from(from_index).unwrap_or(usize::MAX),
+        usize::try_from(limit).unwrap_or(usize::MAX),
+    )
+}
 
 #[derive(BorshSerialize, BorshStorageKey)]
 enum StorageKeys {
     Entities,
     Contributions,
+    /// Prefix of the requests stored before they had IDs. Only read by migrations.
+    #[allow(dead_code)]
     Requests,
     Contributors,
     Needs,
     Invites,
+    EntityContributions,
+    EntityContributionsSet(AccountId),
+    ContributorContributions,
+    ContributorContributionsSet(AccountId),
+    EntityInvites,
+    EntityInvitesSet(AccountId),
+    ContributorInvites,
+    ContributorInvitesSet(AccountId),
+    EntityNeeds,
+    EntityNeedsSet(AccountId),
+    ManagedEntities,
+    ManagedEntitiesSet(AccountId),
+    StorageAccounts,
+    Moderators,
+    ModeratorProposals,
+    StagedCode,
+    ContributionRequests,
+    EntityRequestIds,
+    EntityRequestIdsSet(AccountId),
+    ContributorRequestIds,
+    ContributorRequestIdsSet(AccountId),
+    Endorsements,
+    TargetEndorsements,
+    TargetEndorsementsSet(EndorsementTarget),
+    ContributorEndorsements,
+    ContributorEndorsementsSet(AccountId),
+    Reviews,
+    ContributorRatings,
+    EntityRatings,
+    Bounties,
+    ContributionBounties,
+    Tokens,
+    Escrowed,
+    StoragePayers,
+    Claimable,
 }
 
 #[near_bindgen]
 #[derive(BorshDeserialize, BorshSerialize, PanicOnDefault)]
 pub struct Contract {
-    moderator_id: AccountId,
     entities: UnorderedMap<AccountId, VersionedEntity>,
     contributions: UnorderedMap<(AccountId, AccountId), VersionedContribution>,
-    requests: UnorderedMap<(AccountId, AccountId), VersionedContributionRequest>,
+    requests: UnorderedMap<u64, VersionedContributionRequest>,
+    /// ID the next contribution request gets.
+    next_request_id: u64,
     contributors: UnorderedMap<AccountId, VersionedContributor>,
     needs: UnorderedMap<(AccountId, String), VersionedContributionNeed>,
     invites: UnorderedMap<(AccountId, AccountId), VersionedContributionInvite>,
+    /// Contributors of each entity.
+    entity_contributions: Index<AccountId>,
+    /// Entities each contributor contributes to.
+    contributor_contributions: Index<AccountId>,
+    /// IDs of the requests sent to each entity.
+    entity_requests: Index<u64>,
+    /// IDs of the requests each contributor sent.
+    contributor_requests: Index<u64>,
+    /// Contributors each entity sent an invite to.
+    entity_invites: Index<AccountId>,
+    /// Entities that sent an invite to each contributor.
+    contributor_invites: Index<AccountId>,
+    /// Need CIDs of each entity.
+    entity_needs: Index<String>,
+    /// Entities each account is a manager (or higher) of.
+    managed_entities: Index<AccountId>,
+    /// Storage deposits of accounts (NEP-145).
+    storage_accounts: LookupMap<AccountId, VersionedStorageAccount>,
+    /// Limits for user-supplied strings and sets.
+    validation_config: ValidationConfig,
+    /// Methods paused by the moderator.
+    pause_state: PauseState,
+    /// Members of the moderator council.
+    moderators: UnorderedSet<AccountId>,
+    /// Number of moderator approvals sensitive actions need. One if not set.
+    moderator_threshold: Option<u32>,
+    /// Sensitive actions waiting for more moderator approvals, by the hash of the action.
+    moderator_proposals: UnorderedMap<CryptoHash, VersionedModeratorProposal>,
+    /// Upgrade waiting for its delay to pass.
+    staged_upgrade: Option<StagedUpgrade>,
+    /// Code of the staged upgrade.
+    staged_code: LazyOption<Vec<u8>>,
+    /// Time between staging an upgrade and being able to deploy it.
+    upgrade_delay: Timestamp,
+    /// Batch migration still to be run with `migrate_state`.
+    pending_migration: Option<BatchMigration>,
+    /// Requests stored before they had IDs, until the batch migration moves them.
+    legacy_requests: Option<LegacyRequests>,
+    /// Endorsements by target and endorser.
+    endorsements: LookupMap<(EndorsementTarget, AccountId), VersionedEndorsement>,
+    /// Endorsers of each skill or contribution.
+    target_endorsements: Index<AccountId, EndorsementTarget>,
+    /// Endorsements each contributor received, as target and endorser.
+    contributor_endorsements: Index<(EndorsementTarget, AccountId)>,
+    /// Reviews by entity, contributor, contribution ID and side.
+    reviews: LookupMap<(AccountId, AccountId, u32, ReviewSide), VersionedReview>,
+    /// Ratings each contributor received from entities.
+    contributor_ratings: LookupMap<AccountId, Rating>,
+    /// Ratings each entity received from contributors.
+    entity_ratings: LookupMap<AccountId, Rating>,
+    /// NEAR held in escrow for contribution needs, by entity and need CID.
+    bounties: LookupMap<(AccountId, String), VersionedBounty>,
+    /// Funds held in escrow for contributions, by entity, contributor and contribution ID.
+    contribution_bounties: LookupMap<(AccountId, AccountId, u32), VersionedBounty>,
+    /// Fungible tokens entities can pay with.
+    tokens: UnorderedSet<AccountId>,
+    /// Total amount held in escrow per token.
+    escrowed: LookupMap<Token, Balance>,
+    /// Bytes each account paid for records others can remove, refunded when they are removed.
+    storage_payers: LookupMap<StorageRecord, StoragePayers>,
+    /// Deposits funders can withdraw, by funder and token, after a transfer out of an escrow that
+    /// can no longer be paid out failed.
+    claimable: LookupMap<(AccountId, Token), Balance>,
 }
 
 #[near_bindgen]
 impl Contract {
     #[init]
     pub fn new(moderator_id: AccountId) -> Self {
+        let mut moderators = UnorderedSet::new(StorageKeys::Moderators);
+        moderators.insert(moderator_id);
+        write_state_version();
         Self {
-            moderator_id,
             entities: UnorderedMap::new(StorageKeys::Entities),
             contributions: UnorderedMap::new(StorageKeys::Contributions),
-            requests: UnorderedMap::new(StorageKeys::Requests),
+            requests: UnorderedMap::new(StorageKeys::ContributionRequests),
+            next_request_id: 0,
             contributors: UnorderedMap::new(StorageKeys::Contributors),
             needs: UnorderedMap::new(StorageKeys::Needs),
             invites: UnorderedMap::new(StorageKeys::Invites),
+            entity_contributions: LookupMap::new(StorageKeys::EntityContributions),
+            contributor_contributions: LookupMap::new(StorageKeys::ContributorContributions),
+            entity_requests: LookupMap::new(StorageKeys::EntityRequestIds),
+            contributor_requests: LookupMap::new(StorageKeys::ContributorRequestIds),
+            entity_invites: LookupMap::new(StorageKeys::EntityInvites),
+            contributor_invites: LookupMap::new(StorageKeys::ContributorInvites),
+            entity_needs: LookupMap::new(StorageKeys::EntityNeeds),
+            managed_entities: LookupMap::new(StorageKeys::ManagedEntities),
+            storage_accounts: LookupMap::new(StorageKeys::StorageAccounts),
+            validation_config: ValidationConfig::default(),
+            pause_state: PauseState::default(),
+            moderators,
+            moderator_threshold: None,
+            moderator_proposals: UnorderedMap::new(StorageKeys::ModeratorProposals),
+            staged_upgrade: None,
+            staged_code: LazyOption::new(StorageKeys::StagedCode, None),
+            upgrade_delay: DEFAULT_UPGRADE_DELAY,
+            pending_migration: None,
+            legacy_requests: None,
+            endorsements: LookupMap::new(StorageKeys::Endorsements),
+            target_endorsements: LookupMap::new(StorageKeys::TargetEndorsements),
+            contributor_endorsements: LookupMap::new(StorageKeys::ContributorEndorsements),
+            reviews: LookupMap::new(StorageKeys::Reviews),
+            contributor_ratings: LookupMap::new(StorageKeys::ContributorRatings),
+            entity_ratings: LookupMap::new(StorageKeys::EntityRatings),
+            bounties: LookupMap::new(StorageKeys::Bounties),
+            contribution_bounties: LookupMap::new(StorageKeys::ContributionBounties),
+            tokens: UnorderedSet::new(StorageKeys::Tokens),
+            escrowed: LookupMap::new(StorageKeys::Escrowed),
+            storage_payers: LookupMap::new(StorageKeys::StoragePayers),
+            claimable: LookupMap::new(StorageKeys::Claimable),
         }
     }
 
-    pub fn set_moderator(&mut self, moderator_id: AccountId) {
-        self.assert_moderator();
-        self.moderator_id = moderator_id;
-    }
-
-    /// Assertions.
+    // Assertions
 
-    /// Checks if transaction was performed by moderator account.
+    /// Checks if transaction was performed by a moderator account.
     fn assert_moderator(&self) {
-        // Errors::OnlyModerator.into()
         require!(
-            self.moderator_id == env::predecessor_account_id(),
-            "ERR_ONLY_MODERATOR"
+            self.moderators.contains(&env::predecessor_account_id()),
+            ContractError::OnlyModerator.to_string()
         );
     }
 
-    /// Checks if given account has permissions of a manager or higher for given entity.
-    fn assert_manager_or_higher(&self, entity_id: &AccountId, account_id: &AccountId) {
+    /// Checks if given account has a role with the given capability for given entity.
+    fn assert_capability(
+        &self,
+        entity_id: &AccountId,
+        account_id: &AccountId,
+        capability: Capability,
+    ) {
         require!(
-            self.check_is_manager_or_higher(entity_id, account_id),
-            "ERR_NO_PERMISSION"
+            self.check_has_capability(entity_id, account_id, capability),
+            ContractError::NoPermission.to_string()
         );
     }
 
+    /// Check if given account has a role with the given capability for given entity. Moderators
+    /// have every capability.
+    fn check_has_capability(
+        &self,
+        entity_id: &AccountId,
+        account_id: &AccountId,
+        capability: Capability,
+    ) -> bool {
+        if self.moderators.contains(account_id) {
+            return true;
+        }
+        self.get_role(entity_id.clone(), account_id.clone())
+            .map(|role| role.can(capability))
+            .unwrap_or_default()
+    }
+
     /// Checks if given account is registered as a contributor.
     #[allow(dead_code)]
     fn assert_is_registered(&self, account_id: &AccountId) {
         require!(
             self.contributors.contains_key(account_id),
-            "ERR_NOT_REGISTERED"
+            ContractError::NotRegistered.to_string()
         );
     }
 
-    /// Views
+    // Views
 
-    /// Check if given account ID is moderator.
+    /// Check if given account ID is a member of the moderator council.
     pub fn check_is_moderator(&self, account_id: AccountId) -> bool {
-        self.moderator_id == account_id
+        self.moderators.contains(&account_id)
     }
 
     /// Check if given account ID is manager or higher for given entity.
@@ -100,59 +293,7 @@ impl Contract {
         entity_id: &AccountId,
         account_id: &AccountId,
     ) -> bool {
-        if account_id == &self.moderator_id {
-            return true;
-        }
-        let Some(contribution) = self.contributions.get(&(entity_id.clone(), account_id.clone())) else {
-            return false;
-        };
-        let contribution = Contribution::from(contribution.clone());
-        contribution.permissions.contains(&Permission::Admin)
-    }
-
-    /// Should only be called by this contract on migration.
-    /// This is NOOP implementation. KEEP IT if you haven't changed contract state.
-    /// This method is called from `upgrade()` method.
-    /// For next version upgrades, change this function.
-    #[init(ignore_state)]
-    #[private]
-    pub fn migrate() -> Self {
-        let this: Contract = env::state_read().expect("Contract is not initialized.");
-        this
-    }
-}
-
-#[no_mangle]
-pub fn upgrade() {
-    env::setup_panic_hook();
-
-    let contract: Contract = env::state_read().expect("Contract is not initialized");
-    contract.assert_moderator();
-
-    const MIGRATE_METHOD_NAME: &[u8; 7] = b"migrate";
-    const UPGRADE_GAS_LEFTOVER: Gas = Gas(5_000_000_000_000);
-
-    unsafe {
-        // Load code into register 0 result from the input argument if factory call or from promise if callback.
-        sys::input(0);
-        // Create a promise batch to upgrade current contract with code from register 0.
-        let promise_id = sys::promise_batch_create(
-            env::current_account_id().as_bytes().len() as u64,
-            env::current_account_id().as_bytes().as_ptr() as u64,
-        );
-        // Deploy the contract code from register 0.
-        sys::promise_batch_action_deploy_contract(promise_id, u64::MAX, 0);
-        // Call promise to migrate the state.
-        // Batched together to fail upgrade if migration fails.
-        sys::promise_batch_action_function_call(
-            promise_id,
-            MIGRATE_METHOD_NAME.len() as u64,
-            MIGRATE_METHOD_NAME.as_ptr() as u64,
-            0,
-            0,
-            0,
-            (env::prepaid_gas() - env::used_gas() - UPGRADE_GAS_LEFTOVER).0,
-        );
-        sys::promise_return(promise_id);
+        self.moderators.contains(account_id)
+            || self.get_role(entity_id.clone(), account_id.clone()) >= Some(Permission::Manager)
     }
 }
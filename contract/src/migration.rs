@@ -0,0 +1,485 @@
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::U64;
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::store::{LazyOption, LookupMap, UnorderedMap, UnorderedSet};
+use near_sdk::{env, near_bindgen, AccountId};
+use schemars::JsonSchema;
+
+use crate::contribution::{
+    Contribution, ContributionRequest, VersionedContribution, VersionedContributionInvite,
+    VersionedContributionNeed,
+};
+use crate::contributor::{ContributionType, VersionedContributor};
+use crate::entity::{Permission, VersionedEntity};
+use crate::errors::ContractError;
+use crate::events::Events;
+use crate::pause::PauseState;
+use crate::upgrade::DEFAULT_UPGRADE_DELAY;
+use crate::validation::ValidationConfig;
+use crate::{pagination, Contract, ContractExt, StorageKeys};
+
+/// Version of the `Contract` layout. Bump it whenever the layout of a deployed contract changes,
+/// keep the previous layout as a `ContractV{n}` type and convert it in `migrate`.
+///
+/// Records are versioned separately: when the layout of a record changes, keep the old struct
+/// (e.g. `EntityV1`) in a variant inserted before `Current`, so stored records keep their Borsh
+/// tag, and upgrade it in the `From` conversion of the `Versioned*` enum. Records are then
+/// upgraded lazily when read, and written back in the current layout the next time they are saved.
+pub(crate) const STATE_VERSION: u32 = 2;
+
+/// Storage key of the state version, kept outside of the contract state so it can be read before
+/// knowing the layout. Missing for state version 1.
+const STATE_VERSION_KEY: &[u8] = b"STATE_VERSION";
+
+/// Contract state version 1: before secondary indexes, request IDs, storage accounts, validation
+/// limits, pausing, the moderator council, staged upgrades, endorsements, reviews, bounties and
+/// escrowed tokens were added.
+#[derive(BorshDeserialize)]
+#[cfg_attr(test, derive(BorshSerialize))]
+pub(crate) struct ContractV1 {
+    moderator_id: AccountId,
+    entities: UnorderedMap<AccountId, VersionedEntity>,
+    contributions: UnorderedMap<(AccountId, AccountId), VersionedContribution>,
+    requests: UnorderedMap<(AccountId, AccountId), VersionedContributionRequestV1>,
+    contributors: UnorderedMap<AccountId, VersionedContributor>,
+    needs: UnorderedMap<(AccountId, String), VersionedContributionNeed>,
+    invites: UnorderedMap<(AccountId, AccountId), VersionedContributionInvite>,
+}
+
+impl From<ContractV1> for Contract {
+    /// The old moderator becomes the only member of the moderator council. The secondary indexes
+    /// are built and the requests get IDs in a batch migration.
+    fn from(old: ContractV1) -> Self {
+        let mut moderators = UnorderedSet::new(StorageKeys::Moderators);
+        moderators.insert(old.moderator_id);
+        Self {
+            entities: old.entities,
+            contributions: old.contributions,
+            requests: UnorderedMap::new(StorageKeys::ContributionRequests),
+            next_request_id: 0,
+            contributors: old.contributors,
+            needs: old.needs,
+            invites: old.invites,
+            entity_contributions: LookupMap::new(StorageKeys::EntityContributions),
+            contributor_contributions: LookupMap::new(StorageKeys::ContributorContributions),
+            entity_requests: LookupMap::new(StorageKeys::EntityRequestIds),
+            contributor_requests: LookupMap::new(StorageKeys::ContributorRequestIds),
+            entity_invites: LookupMap::new(StorageKeys::EntityInvites),
+            contributor_invites: LookupMap::new(StorageKeys::ContributorInvites),
+            entity_needs: LookupMap::new(StorageKeys::EntityNeeds),
+            managed_entities: LookupMap::new(StorageKeys::ManagedEntities),
+            storage_accounts: LookupMap::new(StorageKeys::StorageAccounts),
+            validation_config: ValidationConfig::default(),
+            pause_state: PauseState::default(),
+            moderators,
+            moderator_threshold: None,
+            moderator_proposals: UnorderedMap::new(StorageKeys::ModeratorProposals),
+            staged_upgrade: None,
+            staged_code: LazyOption::new(StorageKeys::StagedCode, None),
+            upgrade_delay: DEFAULT_UPGRADE_DELAY,
+            pending_migration: Some(BatchMigration {
+                step: MigrationStep::Contributions,
+                from_index: 0,
+            }),
+            legacy_requests: Some(LegacyRequests {
+                requests: old.requests,
+            }),
+            endorsements: LookupMap::new(StorageKeys::Endorsements),
+            target_endorsements: LookupMap::new(StorageKeys::TargetEndorsements),
+            contributor_endorsements: LookupMap::new(StorageKeys::ContributorEndorsements),
+            reviews: LookupMap::new(StorageKeys::Reviews),
+            contributor_ratings: LookupMap::new(StorageKeys::ContributorRatings),
+            entity_ratings: LookupMap::new(StorageKeys::EntityRatings),
+            bounties: LookupMap::new(StorageKeys::Bounties),
+            contribution_bounties: LookupMap::new(StorageKeys::ContributionBounties),
+            tokens: UnorderedSet::new(StorageKeys::Tokens),
+            escrowed: LookupMap::new(StorageKeys::Escrowed),
+            storage_payers: LookupMap::new(StorageKeys::StoragePayers),
+            claimable: LookupMap::new(StorageKeys::Claimable),
+        }
+    }
+}
+
+/// Request to contribute, as stored before requests had IDs.
+#[derive(BorshSerialize, BorshDeserialize, Clone)]
+pub(crate) struct ContributionRequestV1 {
+    description: String,
+    contribution_type: ContributionType,
+    need: Option<String>,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Clone)]
+pub(crate) enum VersionedContributionRequestV1 {
+    Current(ContributionRequestV1),
+}
+
+/// Requests keyed by entity and contributor, as stored before requests had IDs. Emptied by the
+/// requests step of the batch migration.
+#[derive(BorshSerialize, BorshDeserialize)]
+pub(crate) struct LegacyRequests {
+    requests: UnorderedMap<(AccountId, AccountId), VersionedContributionRequestV1>,
+}
+
+/// Steps of a batch migration, in the order they run.
+#[derive(BorshSerialize, BorshDeserialize, Deserialize, Serialize, Clone, Copy, JsonSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub enum MigrationStep {
+    /// Index the contributions and the roles of the contributors.
+    Contributions,
+    /// Give the contribution requests IDs and index them.
+    Requests,
+    /// Index the invites.
+    Invites,
+    /// Index the contribution needs.
+    Needs,
+    /// Make the founders of entities without an owner their owners.
+    Owners,
+}
+
+impl MigrationStep {
+    fn next(&self) -> Option<Self> {
+        match self {
+            MigrationStep::Contributions => Some(MigrationStep::Requests),
+            MigrationStep::Requests => Some(MigrationStep::Invites),
+            MigrationStep::Invites => Some(MigrationStep::Needs),
+            MigrationStep::Needs => Some(MigrationStep::Owners),
+            MigrationStep::Owners => None,
+        }
+    }
+}
+
+/// Progress of a migration that runs across several `migrate_state` calls.
+#[derive(BorshSerialize, BorshDeserialize, Deserialize, Serialize, Clone, JsonSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct BatchMigration {
+    /// Step being run.
+    pub step: MigrationStep,
+    /// Number of records of the step already migrated.
+    pub from_index: u32,
+}
+
+/// Reads the version of the stored contract state.
+fn read_state_version() -> u32 {
+    env::storage_read(STATE_VERSION_KEY)
+        .map(|version| u32::try_from_slice(&version).unwrap())
+        .unwrap_or(1)
+}
+
+/// Records that the stored contract state has the current layout.
+pub(crate) fn write_state_version() {
+    env::storage_write(STATE_VERSION_KEY, &STATE_VERSION.try_to_vec().unwrap());
+}
+
+impl Contract {
+    /// Migrates at most `limit` records of the step, starting at `from_index`. Returns the number
+    /// of records migrated.
+    fn migrate_step(&mut self, step: MigrationStep, from_index: usize, limit: usize) -> usize {
+        match step {
+            MigrationStep::Contributions => {
+                let contributions: Vec<_> = self
+                    .contributions
+                    .iter()
+                    .skip(from_index)
+                    .take(limit)
+                    .map(|((entity_id, contributor_id), contribution)| {
+                        let contribution = Contribution::from(contribution.clone());
+                        (
+                            entity_id.clone(),
+                            contributor_id.clone(),
+                            Permission::highest(&contribution.permissions),
+                        )
+                    })
+                    .collect();
+                for (entity_id, contributor_id, role) in &contributions {
+                    self.index_contribution(entity_id, contributor_id);
+                    self.index_role(entity_id, contributor_id, role.clone());
+                }
+                contributions.len()
+            }
+            MigrationStep::Requests => {
+                // Requests are moved out of the legacy map, so each batch starts at its beginning.
+                let Some(legacy) = &self.legacy_requests else {
+                    return 0;
+                };
+                let keys: Vec<_> = legacy.requests.keys().take(limit).cloned().collect();
+                for (entity_id, contributor_id) in &keys {
+                    let legacy = self.legacy_requests.as_mut().unwrap();
+                    let Some(VersionedContributionRequestV1::Current(request)) = legacy
+                        .requests
+                        .remove(&(entity_id.clone(), contributor_id.clone()))
+                    else {
+                        continue;
+                    };
+                    self.insert_request(ContributionRequest {
+                        entity_id: entity_id.clone(),
+                        contributor_id: contributor_id.clone(),
+                        description: request.description,
+                        contribution_type: request.contribution_type,
+                        need: request.need,
+                    });
+                }
+                if keys.len() < limit {
+                    self.legacy_requests = None;
+                }
+                keys.len()
+            }
+            MigrationStep::Invites => {
+                let invites: Vec<_> = self
+                    .invites
+                    .keys()
+                    .skip(from_index)
+                    .take(limit)
+                    .cloned()
+                    .collect();
+                for (entity_id, contributor_id) in &invites {
+                    self.index_invite(entity_id, contributor_id);
+                }
+                invites.len()
+            }
+            MigrationStep::Needs => {
+                let needs: Vec<_> = self
+                    .needs
+                    .keys()
+                    .skip(from_index)
+                    .take(limit)
+                    .cloned()
+                    .collect();
+                for (entity_id, cid) in &needs {
+                    self.index_need(entity_id, cid);
+                }
+                needs.len()
+            }
+            MigrationStep::Owners => {
+                let founding_types = [
+                    ContributionType::Founding,
+                    ContributionType::Other("Founding".to_string()),
+                ];
+                let founders: Vec<_> = self
+                    .contributions
+                    .iter()
+                    .skip(from_index)
+                    .take(limit)
+                    .map(|(key, contribution)| {
+                        (key.clone(), Contribution::from(contribution.clone()))
+                    })
+                    .collect();
+                let count = founders.len();
+                for ((entity_id, contributor_id), mut contribution) in founders {
+                    if !contribution.permissions.contains(&Permission::Admin)
+                        || !contribution
+                            .details
+                            .iter()
+                            .any(|detail| founding_types.contains(&detail.contribution_type))
+                        || self.has_owner(&entity_id)
+                    {
+                        continue;
+                    }
+                    contribution.permissions.insert(Permission::Owner);
+                    self.contributions.insert(
+                        (entity_id.clone(), contributor_id.clone()),
+                        VersionedContribution::Current(contribution),
+                    );
+                    self.index_role(&entity_id, &contributor_id, Some(Permission::Owner));
+                }
+                count
+            }
+        }
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Should only be called by this contract on migration.
+    /// This method is called from `deploy_upgrade()` method.
+    /// Converts the stored state from its version to the current layout. Work that doesn't fit in
+    /// one call is left to a batch migration, run with `migrate_state`.
+    #[init(ignore_state)]
+    #[private]
+    pub fn migrate() -> Self {
+        let this = match read_state_version() {
+            1 => Contract::from(
+                env::state_read::<ContractV1>()
+                    .unwrap_or_else(|| ContractError::NotInitialized.panic()),
+            ),
+            STATE_VERSION => {
+                env::state_read().unwrap_or_else(|| ContractError::NotInitialized.panic())
+            }
+            _ => ContractError::UnknownStateVersion.panic(),
+        };
+        write_state_version();
+        Events::Migrate {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            state_version: STATE_VERSION,
+        }
+        .emit();
+        this
+    }
+
+    /// Moderator runs at most `limit` records of the pending batch migration. Returns true once
+    /// there is nothing left to migrate. Other writes are rejected until then.
+    pub fn migrate_state(&mut self, limit: Option<U64>) -> bool {
+        self.assert_moderator();
+        let (_, mut limit) = pagination(None, limit);
+        let mut migrated = 0;
+        while let Some(migration) = self.pending_migration.clone() {
+            if limit == 0 {
+                break;
+            }
+            let count = self.migrate_step(migration.step, migration.from_index as usize, limit);
+            migrated += count;
+            limit -= count;
+            // The step is done once it migrated fewer records than it could.
+            self.pending_migration = if limit > 0 {
+                migration.step.next().map(|step| BatchMigration {
+                    step,
+                    from_index: 0,
+                })
+            } else {
+                Some(BatchMigration {
+                    step: migration.step,
+                    from_index: migration.from_index + count as u32,
+                })
+            };
+        }
+        Events::MigrateState {
+            migrated: migrated as u32,
+            pending: self.pending_migration.clone(),
+        }
+        .emit();
+        self.pending_migration.is_none()
+    }
+
+    // Views
+
+    /// Get the version of the contract state layout.
+    pub fn get_state_version(&self) -> u32 {
+        read_state_version()
+    }
+
+    /// Get the progress of the pending batch migration.
+    pub fn get_pending_migration(&self) -> Option<BatchMigration> {
+        self.pending_migration.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use near_sdk::serde_json::json;
+    use near_sdk::store::UnorderedMap;
+    use near_sdk::{env, serde_json, Timestamp};
+    use std::collections::HashSet;
+
+    use super::*;
+    use crate::contribution::{ContributionDetailV1, ContributionV1};
+    use crate::entity::Entity;
+    use crate::test_utils::*;
+
+    fn founding(start_date: Timestamp) -> ContributionDetailV1 {
+        ContributionDetailV1 {
+            description: "".to_string(),
+            contribution_type: ContributionType::Founding,
+            need: None,
+            start_date,
+            end_date: None,
+        }
+    }
+
+    /// Writes a version 1 state where `owner()` founded `entity()` as its admin and alice sent it
+    /// a request.
+    fn write_v1_state() {
+        set_caller(&contract_id());
+        let mut entities = UnorderedMap::new(StorageKeys::Entities);
+        let record: Entity = serde_json::from_value(json!({
+            "name": "Entity",
+            "status": "Active",
+            "kind": "Project",
+            "start_date": NOW.to_string(),
+            "end_date": "",
+        }))
+        .unwrap();
+        entities.insert(entity(), VersionedEntity::Current(record));
+        let mut contributions = UnorderedMap::new(StorageKeys::Contributions);
+        contributions.insert(
+            (entity(), owner()),
+            VersionedContribution::V1(ContributionV1 {
+                permissions: HashSet::from([Permission::Admin]),
+                current: founding(NOW),
+                history: vec![],
+            }),
+        );
+        let mut requests = UnorderedMap::new(StorageKeys::Requests);
+        requests.insert(
+            (entity(), alice()),
+            VersionedContributionRequestV1::Current(ContributionRequestV1 {
+                description: "Request".to_string(),
+                contribution_type: ContributionType::Development,
+                need: None,
+            }),
+        );
+        let mut state = ContractV1 {
+            moderator_id: moderator(),
+            entities,
+            contributions,
+            requests,
+            contributors: UnorderedMap::new(StorageKeys::Contributors),
+            needs: UnorderedMap::new(StorageKeys::Needs),
+            invites: UnorderedMap::new(StorageKeys::Invites),
+        };
+        state.entities.flush();
+        state.contributions.flush();
+        state.requests.flush();
+        env::state_write(&state);
+    }
+
+    #[test]
+    fn version_1_state_is_migrated() {
+        write_v1_state();
+        let mut contract = Contract::migrate();
+        assert_eq!(contract.get_state_version(), STATE_VERSION);
+        set_caller(&moderator());
+        // The founding contribution, then the request.
+        assert!(!contract.migrate_state(Some(U64(2))));
+        let legacy_requests = &contract.legacy_requests.as_ref().unwrap().requests;
+        assert!(legacy_requests.is_empty());
+        assert_eq!(contract.requests.len(), 1);
+        assert!(contract.requests.contains_key(&0));
+        assert!(contract.migrate_state(None));
+        assert!(contract.legacy_requests.is_none());
+        assert!(contract.get_role(entity(), owner()) == Some(Permission::Owner));
+        assert_eq!(contract.get_managed_entities(owner(), None, None).len(), 1);
+        assert_eq!(
+            contract
+                .get_entity_contributions(entity(), None, None)
+                .len(),
+            1
+        );
+        let requests = contract.get_entity_contribution_requests(entity(), None, None);
+        assert_eq!(requests.len(), 1);
+        let request = contract.get_contribution_request(requests[0]).unwrap();
+        assert_eq!(request.contributor_id, alice());
+        assert!(contract.get_pending_migration().is_none());
+    }
+
+    #[test]
+    fn founders_are_not_made_owners_of_entities_with_an_owner() {
+        let mut contract = setup_with_entity();
+        add_contribution(&mut contract, &alice());
+        grant(&mut contract, &alice(), Permission::Admin);
+        contract.contributions.insert(
+            (entity(), alice()),
+            VersionedContribution::V1(ContributionV1 {
+                permissions: HashSet::from([Permission::Admin]),
+                current: founding(NOW),
+                history: vec![],
+            }),
+        );
+        contract.pending_migration = Some(BatchMigration {
+            step: MigrationStep::Owners,
+            from_index: 0,
+        });
+        set_caller(&moderator());
+        assert!(contract.migrate_state(None));
+        assert!(contract.get_role(entity(), alice()) == Some(Permission::Admin));
+        assert!(contract.get_role(entity(), owner()) == Some(Permission::Owner));
+    }
+}
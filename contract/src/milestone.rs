@@ -0,0 +1,386 @@
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, near_bindgen, require, AccountId, Timestamp};
+use schemars::JsonSchema;
+
+use crate::bounty::Escrow;
+use crate::contribution::{Contribution, VersionedContribution};
+use crate::dec_serde::u64_dec_format;
+use crate::entity::Capability;
+use crate::errors::ContractError;
+use crate::events::Events;
+use crate::pause::PauseCategory;
+use crate::storage::StorageRecord;
+use crate::token::{Payment, Token};
+use crate::{Contract, ContractExt};
+
+/// Progress of a milestone.
+#[derive(
+    BorshSerialize, BorshDeserialize, Deserialize, Serialize, PartialEq, Eq, Clone, JsonSchema,
+)]
+#[serde(crate = "near_sdk::serde")]
+pub enum MilestoneStatus {
+    /// Not submitted by the contributor yet.
+    Pending,
+    /// Submitted by the contributor, waiting for the entity.
+    Submitted,
+    /// Approved by the entity.
+    Approved,
+    /// Rejected by the entity. The contributor can submit it again.
+    Rejected,
+}
+
+/// Step of a contribution, optionally paid from the contribution's escrow when approved.
+#[derive(BorshSerialize, BorshDeserialize, Deserialize, Serialize, Clone, JsonSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Milestone {
+    /// What has to be delivered.
+    pub description: String,
+    /// When the milestone is due.
+    #[serde(with = "u64_dec_format")]
+    #[schemars(with = "String")]
+    pub due_date: Timestamp,
+    /// Paid to the contributor from the contribution's escrow when the milestone is approved.
+    pub payment: Option<Payment>,
+    /// Progress of the milestone.
+    pub status: MilestoneStatus,
+}
+
+impl Contract {
+    /// Checks the milestone can be added to a contribution and marks it as pending.
+    pub(crate) fn new_milestone(&self, milestone: Milestone) -> Milestone {
+        self.assert_valid_description(&milestone.description);
+        if let Some(payment) = &milestone.payment {
+            require!(
+                payment.amount > 0,
+                ContractError::InvalidPayment.to_string()
+            );
+            if let Token::Ft(token_id) = &payment.token {
+                require!(
+                    self.tokens.contains(token_id),
+                    ContractError::TokenNotWhitelisted.to_string()
+                );
+            }
+        }
+        Milestone {
+            status: MilestoneStatus::Pending,
+            ..milestone
+        }
+    }
+
+    /// Moves the milestone of an ongoing contribution from one of the given statuses to the new
+    /// one. Returns the updated milestone.
+    fn set_milestone_status(
+        &mut self,
+        entity_id: &AccountId,
+        contributor_id: &AccountId,
+        contribution_id: u32,
+        milestone_id: u32,
+        from: &[MilestoneStatus],
+        to: MilestoneStatus,
+    ) -> Milestone {
+        let key = (entity_id.clone(), contributor_id.clone());
+        let mut contribution = Contribution::from(
+            self.contributions
+                .get(&key)
+                .unwrap_or_else(|| ContractError::NoContribution.panic())
+                .clone(),
+        );
+        let detail = contribution
+            .details
+            .get_mut(contribution_id as usize)
+            .unwrap_or_else(|| ContractError::NoContribution.panic());
+        require!(
+            detail.end_date.is_none(),
+            ContractError::ContributionFinished.to_string()
+        );
+        let milestone = detail
+            .milestones
+            .get_mut(milestone_id as usize)
+            .unwrap_or_else(|| ContractError::NoMilestone.panic());
+        require!(
+            from.contains(&milestone.status),
+            ContractError::InvalidMilestoneStatus.to_string()
+        );
+        milestone.status = to;
+        let milestone = milestone.clone();
+        self.contributions
+            .insert(key, VersionedContribution::Current(contribution));
+        milestone
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Entity manager (or higher) adds a milestone to an ongoing contribution. The milestone
+    /// starts pending whatever status is given. Returns the ID of the milestone.
+    pub fn add_milestone(
+        &mut self,
+        entity_id: AccountId,
+        contributor_id: AccountId,
+        contribution_id: u32,
+        milestone: Milestone,
+    ) -> u32 {
+        self.assert_not_paused(None);
+        let mut storage_usage = env::storage_usage();
+        self.assert_capability(
+            &entity_id,
+            &env::predecessor_account_id(),
+            Capability::ApproveRequests,
+        );
+        let milestone = self.new_milestone(milestone);
+        let key = (entity_id.clone(), contributor_id.clone());
+        let mut contribution = Contribution::from(
+            self.contributions
+                .get(&key)
+                .unwrap_or_else(|| ContractError::NoContribution.panic())
+                .clone(),
+        );
+        let detail = contribution
+            .details
+            .get_mut(contribution_id as usize)
+            .unwrap_or_else(|| ContractError::NoContribution.panic());
+        require!(
+            detail.end_date.is_none(),
+            ContractError::ContributionFinished.to_string()
+        );
+        detail.milestones.push(milestone.clone());
+        let milestone_id = (detail.milestones.len() - 1) as u32;
+        self.contributions
+            .insert(key, VersionedContribution::Current(contribution));
+        self.charge_record(
+            &StorageRecord::Contribution(entity_id.clone(), contributor_id.clone()),
+            &env::predecessor_account_id(),
+            &mut storage_usage,
+        );
+        Events::AddMilestone {
+            entity_id,
+            contributor_id,
+            contribution_id,
+            milestone_id,
+            milestone,
+        }
+        .emit();
+        milestone_id
+    }
+
+    /// Contributor submits a pending or rejected milestone of their ongoing contribution for the
+    /// entity to approve.
+    pub fn submit_milestone(
+        &mut self,
+        entity_id: AccountId,
+        contribution_id: u32,
+        milestone_id: u32,
+    ) {
+        self.assert_not_paused(Some(PauseCategory::Requests));
+        let mut storage_usage = env::storage_usage();
+        let contributor_id = env::predecessor_account_id();
+        self.set_milestone_status(
+            &entity_id,
+            &contributor_id,
+            contribution_id,
+            milestone_id,
+            &[MilestoneStatus::Pending, MilestoneStatus::Rejected],
+            MilestoneStatus::Submitted,
+        );
+        self.charge_record(
+            &StorageRecord::Contribution(entity_id.clone(), contributor_id.clone()),
+            &contributor_id,
+            &mut storage_usage,
+        );
+        Events::SubmitMilestone {
+            entity_id,
+            contributor_id,
+            contribution_id,
+            milestone_id,
+        }
+        .emit();
+    }
+
+    /// Entity manager (or higher) approves a submitted milestone. Its payment, if any, is released
+    /// from the contribution's escrow to the contributor.
+    pub fn approve_milestone(
+        &mut self,
+        entity_id: AccountId,
+        contributor_id: AccountId,
+        contribution_id: u32,
+        milestone_id: u32,
+    ) {
+        self.assert_not_paused(None);
+        let mut storage_usage = env::storage_usage();
+        self.assert_capability(
+            &entity_id,
+            &env::predecessor_account_id(),
+            Capability::ApproveRequests,
+        );
+        let milestone = self.set_milestone_status(
+            &entity_id,
+            &contributor_id,
+            contribution_id,
+            milestone_id,
+            &[MilestoneStatus::Submitted],
+            MilestoneStatus::Approved,
+        );
+        self.charge_record(
+            &StorageRecord::Contribution(entity_id.clone(), contributor_id.clone()),
+            &env::predecessor_account_id(),
+            &mut storage_usage,
+        );
+        let release = milestone.payment.map(|payment| {
+            self.release_from_bounty(
+                &Escrow::Contribution {
+                    entity_id: entity_id.clone(),
+                    contributor_id: contributor_id.clone(),
+                    contribution_id,
+                },
+                payment,
+                &contributor_id,
+                contribution_id,
+                &mut storage_usage,
+            )
+        });
+        let mut events = vec![Events::ApproveMilestone {
+            entity_id,
+            contributor_id,
+            contribution_id,
+            milestone_id,
+        }];
+        events.extend(release);
+        Events::emit_batch(events);
+    }
+
+    /// Entity manager (or higher) rejects a submitted milestone. The contributor can submit it
+    /// again.
+    pub fn reject_milestone(
+        &mut self,
+        entity_id: AccountId,
+        contributor_id: AccountId,
+        contribution_id: u32,
+        milestone_id: u32,
+        reason: String,
+    ) {
+        self.assert_not_paused(None);
+        let mut storage_usage = env::storage_usage();
+        self.assert_capability(
+            &entity_id,
+            &env::predecessor_account_id(),
+            Capability::ApproveRequests,
+        );
+        self.assert_valid_description(&reason);
+        self.set_milestone_status(
+            &entity_id,
+            &contributor_id,
+            contribution_id,
+            milestone_id,
+            &[MilestoneStatus::Submitted],
+            MilestoneStatus::Rejected,
+        );
+        self.charge_record(
+            &StorageRecord::Contribution(entity_id.clone(), contributor_id.clone()),
+            &env::predecessor_account_id(),
+            &mut storage_usage,
+        );
+        Events::RejectMilestone {
+            entity_id,
+            contributor_id,
+            contribution_id,
+            milestone_id,
+            reason,
+        }
+        .emit();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use near_sdk::json_types::{U128, U64};
+    use near_sdk::ONE_NEAR;
+
+    use crate::bounty::Escrow;
+    use crate::milestone::{Milestone, MilestoneStatus};
+    use crate::test_utils::*;
+    use crate::token::{Payment, Token};
+    use crate::Contract;
+
+    fn contribution_escrow(contribution_id: u32) -> Escrow {
+        Escrow::Contribution {
+            entity_id: entity(),
+            contributor_id: alice(),
+            contribution_id,
+        }
+    }
+
+    fn milestone(token: Token, amount: u128) -> Milestone {
+        Milestone {
+            description: "Milestone".to_string(),
+            due_date: NOW,
+            payment: Some(Payment { token, amount }),
+            status: MilestoneStatus::Pending,
+        }
+    }
+
+    /// Contract where the owner funded alice's ongoing contribution with one NEAR and added a
+    /// milestone paying a quarter of it.
+    fn setup_with_milestone() -> (Contract, u32) {
+        let mut contract = setup_with_entity();
+        let contribution_id = add_contribution(&mut contract, &alice());
+        set_caller_with_deposit(&owner(), ONE_NEAR);
+        contract.fund_escrow(contribution_escrow(contribution_id));
+        set_caller(&owner());
+        contract.add_milestone(
+            entity(),
+            alice(),
+            contribution_id,
+            milestone(Token::Near, ONE_NEAR / 4),
+        );
+        (contract, contribution_id)
+    }
+
+    #[test]
+    fn approved_milestone_is_paid_from_escrow() {
+        let (mut contract, contribution_id) = setup_with_milestone();
+        set_caller(&alice());
+        contract.submit_milestone(entity(), contribution_id, 0);
+        set_caller(&owner());
+        contract.approve_milestone(entity(), alice(), contribution_id, 0);
+        assert_eq!(event_names(), ["approve_milestone", "release_escrow"]);
+        assert_eq!(
+            contract.get_escrow(contribution_escrow(contribution_id)),
+            [(Token::Near, U128(ONE_NEAR / 4 * 3))]
+        );
+    }
+
+    #[test]
+    fn finishing_refunds_payments_of_unapproved_milestones() {
+        let (mut contract, contribution_id) = setup_with_milestone();
+        set_caller(&alice());
+        contract.submit_milestone(entity(), contribution_id, 0);
+        set_caller(&owner());
+        contract.finish_contribution(entity(), alice(), contribution_id, U64(NOW));
+        let events = events();
+        assert_eq!(
+            event_names(),
+            ["finish_contribution", "refund_escrow", "release_escrow"]
+        );
+        assert_eq!(events[1].1["account_id"], owner().to_string());
+        assert_eq!(events[1].1["amount"], (ONE_NEAR / 4).to_string());
+        assert_eq!(events[2].1["amount"], (ONE_NEAR / 4 * 3).to_string());
+        assert!(contract
+            .get_escrow(contribution_escrow(contribution_id))
+            .is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "ERR_TOKEN_NOT_WHITELISTED")]
+    fn milestone_cannot_pay_in_unknown_token() {
+        let mut contract = setup_with_entity();
+        let contribution_id = add_contribution(&mut contract, &alice());
+        set_caller(&owner());
+        contract.add_milestone(
+            entity(),
+            alice(),
+            contribution_id,
+            milestone(Token::Ft("token.near".parse().unwrap()), 100),
+        );
+    }
+}
@@ -0,0 +1,110 @@
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, near_bindgen, require};
+use schemars::JsonSchema;
+use std::collections::HashSet;
+
+use crate::errors::ContractError;
+use crate::events::Events;
+use crate::{Contract, ContractExt};
+
+/// Categories of methods that can be paused separately.
+#[derive(
+    BorshSerialize,
+    BorshDeserialize,
+    Deserialize,
+    Serialize,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    JsonSchema,
+)]
+#[serde(crate = "near_sdk::serde")]
+pub enum PauseCategory {
+    /// Adding entities.
+    EntityCreation,
+    /// Sending, approving and rejecting contribution requests.
+    Requests,
+    /// Sending, accepting and rejecting invites.
+    Invites,
+    /// Posting and editing contribution needs.
+    Needs,
+    /// Registering contributors.
+    Registration,
+    /// Endorsing skills and contributions.
+    Endorsements,
+    /// Reviewing contributions.
+    Reviews,
+}
+
+/// Which methods are currently paused.
+#[derive(BorshSerialize, BorshDeserialize, Deserialize, Serialize, Clone, Default, JsonSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct PauseState {
+    /// Every method except the moderators' is paused.
+    pub all: bool,
+    /// Paused categories of methods.
+    pub categories: HashSet<PauseCategory>,
+}
+
+impl Contract {
+    /// Checks that the contract isn't paused and, if given, that the category isn't paused.
+    /// Moderators aren't affected by the pause, but no one can write while a batch migration is
+    /// pending.
+    pub(crate) fn assert_not_paused(&self, category: Option<PauseCategory>) {
+        require!(
+            self.pending_migration.is_none(),
+            ContractError::MigrationInProgress.to_string()
+        );
+        if self.moderators.contains(&env::predecessor_account_id()) {
+            return;
+        }
+        require!(
+            !self.pause_state.all,
+            ContractError::ContractPaused.to_string()
+        );
+        if let Some(category) = category {
+            require!(
+                !self.pause_state.categories.contains(&category),
+                ContractError::CategoryPaused.to_string()
+            );
+        }
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Moderator pauses the given categories of methods, or the whole contract if none are given.
+    pub fn pause(&mut self, categories: Option<HashSet<PauseCategory>>) {
+        self.assert_moderator();
+        match &categories {
+            Some(categories) => self.pause_state.categories.extend(categories),
+            None => self.pause_state.all = true,
+        }
+        Events::Pause { categories }.emit();
+    }
+
+    /// Moderator unpauses the given categories of methods, or everything if none are given.
+    pub fn unpause(&mut self, categories: Option<HashSet<PauseCategory>>) {
+        self.assert_moderator();
+        match &categories {
+            Some(categories) => self
+                .pause_state
+                .categories
+                .retain(|category| !categories.contains(category)),
+            None => self.pause_state = PauseState::default(),
+        }
+        Events::Unpause { categories }.emit();
+    }
+
+    // Views
+
+    /// Get which methods are currently paused.
+    pub fn get_pause_state(&self) -> PauseState {
+        self.pause_state.clone()
+    }
+}
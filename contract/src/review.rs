@@ -0,0 +1,366 @@
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, near_bindgen, require, AccountId, StorageUsage, Timestamp};
+use schemars::JsonSchema;
+
+use crate::contribution::{Contribution, Verification};
+use crate::dec_serde::u64_dec_format;
+use crate::entity::Capability;
+use crate::errors::ContractError;
+use crate::events::Events;
+use crate::pause::PauseCategory;
+use crate::storage::StorageRecord;
+use crate::{Contract, ContractExt};
+
+/// Highest rating a review can give. The lowest is one.
+pub(crate) const MAX_RATING: u8 = 5;
+
+/// Time after writing a review during which it can still be edited: one week.
+pub(crate) const REVIEW_EDIT_WINDOW: Timestamp = 7 * 24 * 60 * 60 * 1_000_000_000;
+
+/// Who wrote a review of a contribution.
+#[derive(
+    BorshSerialize,
+    BorshDeserialize,
+    Deserialize,
+    Serialize,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Clone,
+    Copy,
+    JsonSchema,
+)]
+#[serde(crate = "near_sdk::serde")]
+pub enum ReviewSide {
+    /// An admin of the entity, reviewing the contributor.
+    Entity,
+    /// The contributor, reviewing the entity.
+    Contributor,
+}
+
+/// Feedback on a finished contribution.
+#[derive(BorshSerialize, BorshDeserialize, Deserialize, Serialize, Clone, JsonSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Review {
+    /// The account that wrote the review.
+    pub reviewer_id: AccountId,
+    /// Rating from one to five.
+    pub rating: u8,
+    /// Short review.
+    pub text: String,
+    /// When the review was first written. It can be edited for a week after that.
+    #[serde(with = "u64_dec_format")]
+    #[schemars(with = "String")]
+    pub created_at: Timestamp,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Clone)]
+pub enum VersionedReview {
+    Current(Review),
+}
+
+impl From<VersionedReview> for Review {
+    fn from(value: VersionedReview) -> Self {
+        match value {
+            VersionedReview::Current(r) => r,
+        }
+    }
+}
+
+/// Aggregate of the ratings an account received.
+#[derive(BorshSerialize, BorshDeserialize, Deserialize, Serialize, Clone, Default, JsonSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Rating {
+    /// Number of reviews.
+    pub count: u32,
+    /// Sum of the ratings of the reviews.
+    pub total: u32,
+}
+
+impl Contract {
+    /// Replaces the old rating of the account with the new one in its aggregate.
+    fn update_rating(
+        &mut self,
+        side: ReviewSide,
+        account_id: &AccountId,
+        old: Option<u8>,
+        new: u8,
+    ) {
+        let ratings = match side {
+            ReviewSide::Entity => &mut self.contributor_ratings,
+            ReviewSide::Contributor => &mut self.entity_ratings,
+        };
+        let rating = ratings.entry(account_id.clone()).or_default();
+        match old {
+            Some(old) => rating.total -= old as u32,
+            None => rating.count += 1,
+        }
+        rating.total += new as u32;
+    }
+
+    /// Takes a removed review's rating out of the aggregate of the account.
+    fn remove_rating(&mut self, side: ReviewSide, account_id: &AccountId, old: u8) {
+        let ratings = match side {
+            ReviewSide::Entity => &mut self.contributor_ratings,
+            ReviewSide::Contributor => &mut self.entity_ratings,
+        };
+        let Some(rating) = ratings.get_mut(account_id) else {
+            return;
+        };
+        rating.count = rating.count.saturating_sub(1);
+        rating.total = rating.total.saturating_sub(old as u32);
+        if rating.count == 0 {
+            ratings.remove(account_id);
+        }
+    }
+
+    /// Removes the reviews of a contribution and their ratings, refunding the reviewers.
+    pub(crate) fn remove_reviews(
+        &mut self,
+        entity_id: &AccountId,
+        contributor_id: &AccountId,
+        contribution_id: u32,
+        storage_usage: &mut StorageUsage,
+    ) -> Vec<Events> {
+        let mut events = vec![];
+        for side in [ReviewSide::Entity, ReviewSide::Contributor] {
+            let key = (
+                entity_id.clone(),
+                contributor_id.clone(),
+                contribution_id,
+                side,
+            );
+            let Some(review) = self.reviews.get(&key).map(|r| Review::from(r.clone())) else {
+                continue;
+            };
+            self.release_record(
+                &StorageRecord::Review(key.0.clone(), key.1.clone(), key.2, key.3),
+                storage_usage,
+                |this| this.reviews.remove(&key),
+            );
+            let reviewed_id = match side {
+                ReviewSide::Entity => contributor_id,
+                ReviewSide::Contributor => entity_id,
+            };
+            self.absorb_storage(storage_usage, |this| {
+                this.remove_rating(side, reviewed_id, review.rating)
+            });
+            events.push(Events::RemoveReview {
+                entity_id: entity_id.clone(),
+                contributor_id: contributor_id.clone(),
+                contribution_id,
+                side,
+            });
+        }
+        events
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Review a verified, finished contribution with a rating from one to five and a short text. The
+    /// contributor reviews the entity, entity admins (or higher) review the contributor. Reviews
+    /// can be edited for a week after they are written.
+    pub fn review_contribution(
+        &mut self,
+        entity_id: AccountId,
+        contributor_id: AccountId,
+        contribution_id: u32,
+        rating: u8,
+        text: String,
+    ) {
+        self.assert_not_paused(Some(PauseCategory::Reviews));
+        let mut storage_usage = env::storage_usage();
+        let reviewer_id = env::predecessor_account_id();
+        let side = if reviewer_id == contributor_id {
+            ReviewSide::Contributor
+        } else {
+            self.assert_capability(&entity_id, &reviewer_id, Capability::ReviewContributions);
+            ReviewSide::Entity
+        };
+        require!(
+            (1..=MAX_RATING).contains(&rating),
+            ContractError::InvalidRating.to_string()
+        );
+        self.assert_valid_description(&text);
+        let contribution = Contribution::from(
+            self.contributions
+                .get(&(entity_id.clone(), contributor_id.clone()))
+                .unwrap_or_else(|| ContractError::NoContribution.panic())
+                .clone(),
+        );
+        let detail = contribution
+            .details
+            .get(contribution_id as usize)
+            .unwrap_or_else(|| ContractError::NoContribution.panic());
+        // Claims anyone can make aren't reviewed, so they can't be used to sink a rating.
+        require!(
+            detail.verification == Verification::Verified,
+            ContractError::ContributionNotVerified.to_string()
+        );
+        require!(
+            detail.end_date.is_some(),
+            ContractError::ContributionNotFinished.to_string()
+        );
+        let key = (
+            entity_id.clone(),
+            contributor_id.clone(),
+            contribution_id,
+            side,
+        );
+        let old = self
+            .reviews
+            .get(&key)
+            .map(|review| Review::from(review.clone()));
+        if let Some(old) = &old {
+            require!(
+                env::block_timestamp() <= old.created_at + REVIEW_EDIT_WINDOW,
+                ContractError::ReviewLocked.to_string()
+            );
+        }
+        let review = Review {
+            reviewer_id: reviewer_id.clone(),
+            rating,
+            text,
+            created_at: old
+                .as_ref()
+                .map(|old| old.created_at)
+                .unwrap_or(env::block_timestamp()),
+        };
+        self.reviews
+            .insert(key.clone(), VersionedReview::Current(review.clone()));
+        self.charge_record(
+            &StorageRecord::Review(key.0, key.1, key.2, key.3),
+            &reviewer_id,
+            &mut storage_usage,
+        );
+        let reviewed_id = match side {
+            ReviewSide::Entity => &contributor_id,
+            ReviewSide::Contributor => &entity_id,
+        };
+        // Ratings are shared by every reviewer, the contract pays for them.
+        self.absorb_storage(&mut storage_usage, |this| {
+            this.update_rating(
+                side,
+                reviewed_id,
+                old.as_ref().map(|old| old.rating),
+                rating,
+            )
+        });
+        Events::ReviewContribution {
+            entity_id,
+            contributor_id,
+            contribution_id,
+            side,
+            old,
+            new: review,
+        }
+        .emit();
+    }
+
+    // Views
+
+    /// Get the review of a contribution written by the given side.
+    pub fn get_review(
+        &self,
+        entity_id: AccountId,
+        contributor_id: AccountId,
+        contribution_id: u32,
+        side: ReviewSide,
+    ) -> Option<Review> {
+        self.reviews
+            .get(&(entity_id, contributor_id, contribution_id, side))
+            .map(|review| review.clone().into())
+    }
+
+    /// Get the aggregate of the ratings entities gave to the contributor.
+    pub fn get_contributor_rating(&self, account_id: AccountId) -> Rating {
+        self.contributor_ratings
+            .get(&account_id)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Get the aggregate of the ratings contributors gave to the entity.
+    pub fn get_entity_rating(&self, account_id: AccountId) -> Rating {
+        self.entity_ratings
+            .get(&account_id)
+            .cloned()
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use near_sdk::json_types::U64;
+
+    use crate::contributor::ContributionType;
+    use crate::review::ReviewSide;
+    use crate::test_utils::*;
+
+    #[test]
+    fn both_sides_review_finished_contributions() {
+        let mut contract = setup_with_entity();
+        let contribution_id = add_contribution(&mut contract, &alice());
+        set_caller(&owner());
+        contract.finish_contribution(entity(), alice(), contribution_id, U64(NOW));
+        set_caller(&alice());
+        contract.review_contribution(entity(), alice(), contribution_id, 4, "Good".to_string());
+        set_caller(&owner());
+        contract.review_contribution(entity(), alice(), contribution_id, 2, "Late".to_string());
+        assert_eq!(contract.get_entity_rating(entity()).total, 4);
+        assert_eq!(contract.get_contributor_rating(alice()).total, 2);
+        assert!(contract
+            .get_review(entity(), alice(), contribution_id, ReviewSide::Entity)
+            .is_some());
+    }
+
+    #[test]
+    #[should_panic(expected = "ERR_CONTRIBUTION_NOT_FINISHED")]
+    fn ongoing_contribution_cannot_be_reviewed() {
+        let mut contract = setup_with_entity();
+        let contribution_id = add_contribution(&mut contract, &alice());
+        set_caller(&alice());
+        contract.review_contribution(entity(), alice(), contribution_id, 1, "Bad".to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "ERR_CONTRIBUTION_NOT_VERIFIED")]
+    fn unverified_claim_cannot_be_reviewed() {
+        let mut contract = setup_with_entity();
+        deposit_storage(&mut contract, &alice());
+        set_caller(&alice());
+        let contribution_id = contract.claim_contribution(
+            entity(),
+            "Claim".to_string(),
+            ContributionType::Development,
+            U64(0),
+            U64(NOW),
+        );
+        set_caller(&alice());
+        contract.review_contribution(entity(), alice(), contribution_id, 1, "Bad".to_string());
+    }
+
+    #[test]
+    fn reviews_and_ratings_are_removed_with_the_entity() {
+        let mut contract = setup_with_entity();
+        let contribution_id = add_contribution(&mut contract, &alice());
+        set_caller(&owner());
+        contract.finish_contribution(entity(), alice(), contribution_id, U64(NOW));
+        set_caller(&alice());
+        contract.review_contribution(entity(), alice(), contribution_id, 4, "Good".to_string());
+        set_caller(&owner());
+        contract.review_contribution(entity(), alice(), contribution_id, 2, "Late".to_string());
+        assert!(contract.remove_entity(entity(), None));
+        for side in [ReviewSide::Entity, ReviewSide::Contributor] {
+            assert!(contract
+                .get_review(entity(), alice(), contribution_id, side)
+                .is_none());
+        }
+        assert_eq!(contract.get_entity_rating(entity()).count, 0);
+        assert_eq!(contract.get_contributor_rating(alice()).count, 0);
+        assert!(!contract.contributor_ratings.contains_key(&alice()));
+    }
+}
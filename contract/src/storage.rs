@@ -0,0 +1,507 @@
+use near_contract_standards::storage_management::{
+    StorageBalance, StorageBalanceBounds, StorageManagement,
+};
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::U128;
+use near_sdk::{
+    assert_one_yocto, env, near_bindgen, require, AccountId, Balance, Promise, StorageUsage,
+};
+use std::collections::BTreeMap;
+
+use crate::bounty::Escrow;
+use crate::endorsement::EndorsementTarget;
+use crate::errors::ContractError;
+use crate::events::Events;
+use crate::review::ReviewSide;
+use crate::{Contract, ContractExt};
+
+/// Bytes reserved for the storage account record itself. Covered by the minimum deposit.
+const ACCOUNT_STORAGE_BYTES: StorageUsage = 200;
+
+/// Storage deposit of an account and the number of bytes of state it is responsible for.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Default)]
+pub struct StorageAccount {
+    /// Total NEAR deposited for storage.
+    pub balance: Balance,
+    /// Bytes of contract state created by this account (excluding the account record).
+    pub used_bytes: StorageUsage,
+}
+
+impl StorageAccount {
+    /// Balance locked for the state this account uses.
+    fn locked(&self) -> Balance {
+        Balance::from(ACCOUNT_STORAGE_BYTES + self.used_bytes) * env::storage_byte_cost()
+    }
+
+    /// Balance that can still be used for new state or withdrawn.
+    fn available(&self) -> Balance {
+        self.balance.saturating_sub(self.locked())
+    }
+}
+
+impl From<StorageAccount> for StorageBalance {
+    fn from(value: StorageAccount) -> Self {
+        StorageBalance {
+            total: value.balance.into(),
+            available: value.available().into(),
+        }
+    }
+}
+
+/// Record that several accounts can pay the storage of, and that can be removed by someone else
+/// than its payers. The bytes each account paid are refunded to it when the record is removed.
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Eq, PartialOrd, Ord, Clone)]
+pub enum StorageRecord {
+    Entity(AccountId),
+    /// Contribution by entity and contributor, with the contributor's indexes.
+    Contribution(AccountId, AccountId),
+    Request(u64),
+    /// Need by entity and CID.
+    Need(AccountId, String),
+    /// Invite by entity and contributor.
+    Invite(AccountId, AccountId),
+    Escrow(Escrow),
+    /// Endorsement by target and endorser.
+    Endorsement(EndorsementTarget, AccountId),
+    /// Review by entity, contributor, contribution ID and side.
+    Review(AccountId, AccountId, u32, ReviewSide),
+}
+
+/// Bytes of a record paid by each account.
+pub type StoragePayers = BTreeMap<AccountId, StorageUsage>;
+
+#[derive(BorshSerialize, BorshDeserialize, Clone)]
+pub enum VersionedStorageAccount {
+    Current(StorageAccount),
+}
+
+impl From<VersionedStorageAccount> for StorageAccount {
+    fn from(value: VersionedStorageAccount) -> Self {
+        match value {
+            VersionedStorageAccount::Current(a) => a,
+        }
+    }
+}
+
+impl Contract {
+    fn get_storage_account(&self, account_id: &AccountId) -> Option<StorageAccount> {
+        self.storage_accounts
+            .get(account_id)
+            .map(|account| account.clone().into())
+    }
+
+    /// Writes all cached collection changes so `env::storage_usage` reflects them.
+    fn flush_state(&mut self) {
+        self.entities.flush();
+        self.contributions.flush();
+        self.requests.flush();
+        self.contributors.flush();
+        self.needs.flush();
+        self.invites.flush();
+        self.entity_contributions.flush();
+        self.contributor_contributions.flush();
+        self.entity_requests.flush();
+        self.contributor_requests.flush();
+        self.entity_invites.flush();
+        self.contributor_invites.flush();
+        self.entity_needs.flush();
+        self.managed_entities.flush();
+        self.storage_accounts.flush();
+        self.moderators.flush();
+        self.moderator_proposals.flush();
+        self.staged_code.flush();
+        self.endorsements.flush();
+        self.target_endorsements.flush();
+        self.contributor_endorsements.flush();
+        self.reviews.flush();
+        self.contributor_ratings.flush();
+        self.entity_ratings.flush();
+        self.bounties.flush();
+        self.contribution_bounties.flush();
+        self.tokens.flush();
+        self.escrowed.flush();
+        self.storage_payers.flush();
+        self.claimable.flush();
+    }
+
+    /// Adds the bytes to the state the account uses. Panics if the account's storage deposit
+    /// doesn't cover it, unless `enforce` is false for writes that can't fail, like callbacks.
+    fn use_bytes(&mut self, account_id: &AccountId, bytes: StorageUsage, enforce: bool) {
+        let Some(mut account) = self.get_storage_account(account_id) else {
+            require!(
+                bytes == 0 || !enforce,
+                ContractError::InsufficientStorage.to_string()
+            );
+            return;
+        };
+        account.used_bytes += bytes;
+        require!(
+            account.balance >= account.locked() || !enforce,
+            ContractError::InsufficientStorage.to_string()
+        );
+        self.storage_accounts.insert(
+            account_id.clone(),
+            VersionedStorageAccount::Current(account),
+        );
+    }
+
+    /// Removes the bytes from the state the account uses.
+    fn free_bytes(&mut self, account_id: &AccountId, bytes: StorageUsage) {
+        let Some(mut account) = self.get_storage_account(account_id) else {
+            return;
+        };
+        account.used_bytes = account.used_bytes.saturating_sub(bytes);
+        self.storage_accounts.insert(
+            account_id.clone(),
+            VersionedStorageAccount::Current(account),
+        );
+    }
+
+    /// Charges the account for the state created since `initial_storage_usage` was measured, or
+    /// credits it for the state freed. Panics if the account's storage deposit doesn't cover the
+    /// state it uses. Moderators are not charged. State shared with other accounts goes through
+    /// `charge_record` instead.
+    pub(crate) fn charge_storage(
+        &mut self,
+        account_id: &AccountId,
+        initial_storage_usage: StorageUsage,
+    ) {
+        if self.moderators.contains(account_id) {
+            return;
+        }
+        self.flush_state();
+        let storage_usage = env::storage_usage();
+        if storage_usage > initial_storage_usage {
+            self.use_bytes(account_id, storage_usage - initial_storage_usage, true);
+        } else {
+            self.free_bytes(account_id, initial_storage_usage - storage_usage);
+        }
+    }
+
+    /// Charges the account for the state the record grew by since `storage_usage` was measured,
+    /// and adds it to what the account paid for the record. If the record shrank, the freed bytes
+    /// are credited to its payers, the account first. Moderators are not charged. Moves
+    /// `storage_usage` to the current usage.
+    pub(crate) fn charge_record(
+        &mut self,
+        record: &StorageRecord,
+        account_id: &AccountId,
+        storage_usage: &mut StorageUsage,
+    ) {
+        self.settle_record(record, account_id, storage_usage, true);
+    }
+
+    /// Like `charge_record`, for callbacks: the account is charged even if its storage deposit
+    /// doesn't cover it, so the callback can't fail.
+    pub(crate) fn charge_record_unchecked(
+        &mut self,
+        record: &StorageRecord,
+        account_id: &AccountId,
+        storage_usage: &mut StorageUsage,
+    ) {
+        self.settle_record(record, account_id, storage_usage, false);
+    }
+
+    fn settle_record(
+        &mut self,
+        record: &StorageRecord,
+        account_id: &AccountId,
+        storage_usage: &mut StorageUsage,
+        enforce: bool,
+    ) {
+        self.flush_state();
+        let current = env::storage_usage();
+        let mut payers = self.storage_payers.get(record).cloned().unwrap_or_default();
+        if current > *storage_usage {
+            if !self.moderators.contains(account_id) {
+                let paid = payers.get(account_id).copied().unwrap_or_default();
+                // The payer entry takes up storage too, so it's measured once written.
+                payers.insert(account_id.clone(), paid);
+                self.storage_payers.insert(record.clone(), payers.clone());
+                self.flush_state();
+                let bytes = env::storage_usage() - *storage_usage;
+                payers.insert(account_id.clone(), paid + bytes);
+                self.storage_payers.insert(record.clone(), payers);
+                self.use_bytes(account_id, bytes, enforce);
+            }
+        } else if current < *storage_usage {
+            let mut freed = *storage_usage - current;
+            let mut order: Vec<AccountId> = payers.keys().cloned().collect();
+            order.sort_by_key(|payer_id| payer_id != account_id);
+            for payer_id in order {
+                let paid = payers.get_mut(&payer_id).unwrap();
+                let credit = freed.min(*paid);
+                *paid -= credit;
+                freed -= credit;
+                self.free_bytes(&payer_id, credit);
+            }
+            payers.retain(|_, paid| *paid > 0);
+            if payers.is_empty() {
+                self.storage_payers.remove(record);
+            } else {
+                self.storage_payers.insert(record.clone(), payers);
+            }
+        }
+        self.flush_state();
+        *storage_usage = env::storage_usage();
+    }
+
+    /// Removes a record with `remove` and refunds its payers the bytes they paid for it. The
+    /// freed state is taken out of what the caller is charged for.
+    pub(crate) fn release_record<R>(
+        &mut self,
+        record: &StorageRecord,
+        storage_usage: &mut StorageUsage,
+        remove: impl FnOnce(&mut Self) -> R,
+    ) -> R {
+        self.absorb_storage(storage_usage, |this| {
+            let result = remove(this);
+            let payers = this.storage_payers.remove(record).unwrap_or_default();
+            for (payer_id, paid) in payers {
+                this.free_bytes(&payer_id, paid);
+            }
+            result
+        })
+    }
+
+    /// Makes a change to state the contract pays for itself, so it isn't charged to the caller
+    /// or credited to it.
+    pub(crate) fn absorb_storage<R>(
+        &mut self,
+        storage_usage: &mut StorageUsage,
+        write: impl FnOnce(&mut Self) -> R,
+    ) -> R {
+        self.flush_state();
+        let before = env::storage_usage();
+        let result = write(self);
+        self.flush_state();
+        let after = env::storage_usage();
+        if after > before {
+            *storage_usage += after - before;
+        } else {
+            *storage_usage = storage_usage.saturating_sub(before - after);
+        }
+        result
+    }
+}
+
+#[near_bindgen]
+impl StorageManagement for Contract {
+    /// Deposit NEAR to cover the storage of the given account (predecessor by default).
+    #[payable]
+    fn storage_deposit(
+        &mut self,
+        account_id: Option<AccountId>,
+        registration_only: Option<bool>,
+    ) -> StorageBalance {
+        self.assert_not_paused(None);
+        let amount = env::attached_deposit();
+        let account_id = account_id.unwrap_or_else(env::predecessor_account_id);
+        let min_balance = self.storage_balance_bounds().min.0;
+        let (account, refund) = match self.get_storage_account(&account_id) {
+            Some(account) if registration_only.unwrap_or_default() => (account, amount),
+            Some(mut account) => {
+                account.balance += amount;
+                (account, 0)
+            }
+            None => {
+                require!(
+                    amount >= min_balance,
+                    ContractError::DepositLessThanMinStorage.to_string()
+                );
+                let balance = if registration_only.unwrap_or_default() {
+                    min_balance
+                } else {
+                    amount
+                };
+                (
+                    StorageAccount {
+                        balance,
+                        used_bytes: 0,
+                    },
+                    amount - balance,
+                )
+            }
+        };
+        self.storage_accounts.insert(
+            account_id.clone(),
+            VersionedStorageAccount::Current(account.clone()),
+        );
+        if refund > 0 {
+            Promise::new(env::predecessor_account_id()).transfer(refund);
+        }
+        Events::StorageDeposit {
+            account_id,
+            amount: amount - refund,
+        }
+        .emit();
+        account.into()
+    }
+
+    /// Withdraw available storage deposit (all of it by default).
+    #[payable]
+    fn storage_withdraw(&mut self, amount: Option<U128>) -> StorageBalance {
+        self.assert_not_paused(None);
+        assert_one_yocto();
+        let account_id = env::predecessor_account_id();
+        let mut account = self
+            .get_storage_account(&account_id)
+            .unwrap_or_else(|| ContractError::NotRegisteredForStorage.panic());
+        let available = account.available();
+        let amount = amount.map(u128::from).unwrap_or(available);
+        require!(
+            amount <= available,
+            ContractError::WithdrawMoreThanAvailable.to_string()
+        );
+        account.balance -= amount;
+        self.storage_accounts.insert(
+            account_id.clone(),
+            VersionedStorageAccount::Current(account.clone()),
+        );
+        if amount > 0 {
+            Promise::new(account_id.clone()).transfer(amount);
+        }
+        Events::StorageWithdraw { account_id, amount }.emit();
+        account.into()
+    }
+
+    /// Unregister and withdraw the whole storage deposit. Only possible once the account doesn't
+    /// use any state. Force unregistration is not supported.
+    #[payable]
+    fn storage_unregister(&mut self, force: Option<bool>) -> bool {
+        self.assert_not_paused(None);
+        assert_one_yocto();
+        require!(
+            !force.unwrap_or_default(),
+            ContractError::ForceUnregisterNotSupported.to_string()
+        );
+        let account_id = env::predecessor_account_id();
+        let Some(account) = self.get_storage_account(&account_id) else {
+            return false;
+        };
+        require!(
+            account.used_bytes == 0,
+            ContractError::StorageInUse.to_string()
+        );
+        self.storage_accounts.remove(&account_id);
+        if account.balance > 0 {
+            Promise::new(account_id.clone()).transfer(account.balance);
+        }
+        Events::StorageUnregister {
+            account_id,
+            amount: account.balance,
+        }
+        .emit();
+        true
+    }
+
+    fn storage_balance_bounds(&self) -> StorageBalanceBounds {
+        StorageBalanceBounds {
+            min: (Balance::from(ACCOUNT_STORAGE_BYTES) * env::storage_byte_cost()).into(),
+            max: None,
+        }
+    }
+
+    fn storage_balance_of(&self, account_id: AccountId) -> Option<StorageBalance> {
+        self.get_storage_account(&account_id).map(Into::into)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use near_contract_standards::storage_management::StorageManagement;
+    use near_sdk::json_types::U64;
+    use near_sdk::{AccountId, StorageUsage, ONE_NEAR};
+    use std::collections::HashSet;
+
+    use crate::bounty::Escrow;
+    use crate::contributor::ContributionType;
+    use crate::entity::Permission;
+    use crate::test_utils::*;
+    use crate::Contract;
+
+    fn used_bytes(contract: &Contract, account_id: &AccountId) -> StorageUsage {
+        contract.get_storage_account(account_id).unwrap().used_bytes
+    }
+
+    fn request(contract: &mut Contract, contributor_id: &AccountId) -> U64 {
+        deposit_storage(contract, contributor_id);
+        set_caller(contributor_id);
+        contract.request_contribution(
+            entity(),
+            "Contribution".to_string(),
+            ContributionType::Development,
+            None,
+        )
+    }
+
+    #[test]
+    fn rejected_request_is_refunded_to_requester() {
+        let mut contract = setup_with_entity();
+        let request_id = request(&mut contract, &alice());
+        assert!(used_bytes(&contract, &alice()) > 0);
+        let owner_bytes = used_bytes(&contract, &owner());
+        set_caller(&owner());
+        contract.reject_contribution(request_id);
+        assert_eq!(used_bytes(&contract, &alice()), 0);
+        assert_eq!(used_bytes(&contract, &owner()), owner_bytes);
+        set_caller_with_deposit(&alice(), 1);
+        assert!(contract.storage_unregister(None));
+    }
+
+    #[test]
+    fn cancelled_invite_is_refunded_to_inviter() {
+        let mut contract = setup_with_entity();
+        let owner_bytes = used_bytes(&contract, &owner());
+        set_caller(&owner());
+        contract.invite_contributor(
+            entity(),
+            bob(),
+            "Invite".to_string(),
+            ContributionType::Development,
+            U64(NOW),
+            HashSet::new(),
+        );
+        assert!(used_bytes(&contract, &owner()) > owner_bytes);
+        deposit_storage(&mut contract, &bob());
+        set_caller(&bob());
+        contract.reject_invite(entity());
+        assert_eq!(used_bytes(&contract, &owner()), owner_bytes);
+        assert_eq!(used_bytes(&contract, &bob()), 0);
+    }
+
+    #[test]
+    fn contribution_is_refunded_to_its_payers_when_the_entity_is_removed() {
+        let mut contract = setup_with_entity();
+        request(&mut contract, &bob());
+        add_contribution(&mut contract, &alice());
+        grant(&mut contract, &alice(), Permission::Manager);
+        set_caller(&owner());
+        assert!(contract.remove_entity(entity(), None));
+        assert_eq!(used_bytes(&contract, &alice()), 0);
+        assert_eq!(used_bytes(&contract, &bob()), 0);
+        assert_eq!(used_bytes(&contract, &owner()), 0);
+    }
+
+    #[test]
+    fn escrow_is_refunded_to_funder_when_cancelled_by_another_manager() {
+        let mut contract = setup_with_entity();
+        let contribution_id = add_contribution(&mut contract, &alice());
+        add_contribution(&mut contract, &bob());
+        grant(&mut contract, &bob(), Permission::Manager);
+        let escrow = Escrow::Contribution {
+            entity_id: entity(),
+            contributor_id: alice(),
+            contribution_id,
+        };
+        let bob_bytes = used_bytes(&contract, &bob());
+        set_caller_with_deposit(&bob(), ONE_NEAR);
+        contract.fund_escrow(escrow);
+        assert!(used_bytes(&contract, &bob()) > bob_bytes);
+        let owner_bytes = used_bytes(&contract, &owner());
+        set_caller(&owner());
+        contract.cancel_contribution_escrow(entity(), alice(), contribution_id);
+        assert_eq!(used_bytes(&contract, &bob()), bob_bytes);
+        assert_eq!(used_bytes(&contract, &owner()), owner_bytes);
+    }
+}
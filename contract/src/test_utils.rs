@@ -0,0 +1,133 @@
+//! Helpers shared by the unit tests.
+
+use near_contract_standards::storage_management::StorageManagement;
+use near_sdk::json_types::U64;
+use near_sdk::serde_json::{self, Value};
+use near_sdk::test_utils::{get_logs, VMContextBuilder};
+use near_sdk::{testing_env, AccountId, Balance, VMContext, ONE_NEAR};
+
+use crate::contributor::ContributionType;
+use crate::entity::{EntityKind, Permission};
+use crate::Contract;
+
+/// Block time the tests run at.
+pub(crate) const NOW: u64 = 1_000_000_000_000;
+
+pub(crate) fn contract_id() -> AccountId {
+    "contract.near".parse().unwrap()
+}
+
+pub(crate) fn moderator() -> AccountId {
+    "moderator.near".parse().unwrap()
+}
+
+pub(crate) fn entity() -> AccountId {
+    "entity.near".parse().unwrap()
+}
+
+/// Owner of `entity()`.
+pub(crate) fn owner() -> AccountId {
+    "owner.near".parse().unwrap()
+}
+
+pub(crate) fn alice() -> AccountId {
+    "alice.near".parse().unwrap()
+}
+
+pub(crate) fn bob() -> AccountId {
+    "bob.near".parse().unwrap()
+}
+
+fn context(predecessor_id: &AccountId, deposit: Balance) -> VMContext {
+    VMContextBuilder::new()
+        .current_account_id(contract_id())
+        .signer_account_id(predecessor_id.clone())
+        .predecessor_account_id(predecessor_id.clone())
+        .attached_deposit(deposit)
+        .block_timestamp(NOW)
+        .build()
+}
+
+/// Makes the next calls come from the account, without a deposit.
+pub(crate) fn set_caller(predecessor_id: &AccountId) {
+    set_caller_with_deposit(predecessor_id, 0);
+}
+
+/// Makes the next calls come from the account, with the deposit attached.
+pub(crate) fn set_caller_with_deposit(predecessor_id: &AccountId, deposit: Balance) {
+    testing_env!(context(predecessor_id, deposit));
+}
+
+/// Contract with `moderator()` as the only moderator.
+pub(crate) fn setup() -> Contract {
+    set_caller(&moderator());
+    Contract::new(moderator())
+}
+
+/// Deposits enough NEAR to cover the storage the account uses in the tests.
+pub(crate) fn deposit_storage(contract: &mut Contract, account_id: &AccountId) {
+    set_caller_with_deposit(account_id, ONE_NEAR);
+    contract.storage_deposit(None, None);
+}
+
+/// Contract with `entity()` founded by `owner()`.
+pub(crate) fn setup_with_entity() -> Contract {
+    let mut contract = setup();
+    deposit_storage(&mut contract, &owner());
+    set_caller(&owner());
+    contract.add_entity(
+        entity(),
+        "Entity".to_string(),
+        EntityKind::Project,
+        U64(NOW),
+    );
+    contract
+}
+
+/// The contributor requests to contribute to `entity()` and `owner()` approves it. Returns the
+/// ID of the contribution.
+pub(crate) fn add_contribution(contract: &mut Contract, contributor_id: &AccountId) -> u32 {
+    deposit_storage(contract, contributor_id);
+    set_caller(contributor_id);
+    let request_id = contract.request_contribution(
+        entity(),
+        "Contribution".to_string(),
+        ContributionType::Development,
+        None,
+    );
+    set_caller(&owner());
+    contract.approve_contribution(request_id, None, None, None);
+    contract
+        .get_contribution(entity(), contributor_id.clone())
+        .unwrap()
+        .details
+        .len() as u32
+        - 1
+}
+
+/// Gives the contributor of `entity()` the permission.
+pub(crate) fn grant(contract: &mut Contract, contributor_id: &AccountId, permission: Permission) {
+    set_caller(&owner());
+    contract.grant_permission(entity(), contributor_id.clone(), permission);
+}
+
+/// Events logged by the last call, as name and data.
+pub(crate) fn events() -> Vec<(String, Value)> {
+    get_logs()
+        .iter()
+        .filter_map(|log| log.strip_prefix("EVENT_JSON:"))
+        .flat_map(|log| {
+            let mut log: Value = serde_json::from_str(log).unwrap();
+            let name = log["event"].as_str().unwrap().to_string();
+            let Value::Array(data) = log["data"].take() else {
+                unreachable!();
+            };
+            data.into_iter().map(move |data| (name.clone(), data))
+        })
+        .collect()
+}
+
+/// Names of the events logged by the last call.
+pub(crate) fn event_names() -> Vec<String> {
+    events().into_iter().map(|(name, _)| name).collect()
+}
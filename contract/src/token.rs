@@ -0,0 +1,499 @@
+use near_contract_standards::fungible_token::core::ext_ft_core;
+use near_contract_standards::fungible_token::receiver::FungibleTokenReceiver;
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::{U128, U64};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{
+    assert_one_yocto, env, near_bindgen, require, serde_json, AccountId, Balance, Gas, Promise,
+    PromiseOrValue, PromiseResult, StorageUsage,
+};
+use schemars::JsonSchema;
+
+use crate::bounty::Escrow;
+use crate::contribution::{Contribution, VersionedContribution};
+use crate::dec_serde::u128_dec_format;
+use crate::errors::ContractError;
+use crate::events::Events;
+use crate::pause::PauseCategory;
+use crate::storage::StorageRecord;
+use crate::{pagination, Contract, ContractExt};
+
+/// Gas for the `ft_transfer` call to a token contract.
+const GAS_FOR_FT_TRANSFER: Gas = Gas(10_000_000_000_000);
+
+/// Gas for the callback resolving a payout or a withdrawal.
+const GAS_FOR_RESOLVE_TRANSFER: Gas = Gas(15_000_000_000_000);
+
+/// Asset that entities pay contributors with.
+#[derive(
+    BorshSerialize,
+    BorshDeserialize,
+    Deserialize,
+    Serialize,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Clone,
+    Debug,
+    JsonSchema,
+)]
+#[serde(crate = "near_sdk::serde")]
+pub enum Token {
+    /// Native NEAR.
+    Near,
+    /// Whitelisted NEP-141 fungible token, by contract account.
+    Ft(AccountId),
+}
+
+/// Payment a contributor received for a contribution.
+#[derive(BorshSerialize, BorshDeserialize, Deserialize, Serialize, Clone, JsonSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Payment {
+    /// The token that was paid.
+    pub token: Token,
+    /// The amount that was paid.
+    #[serde(with = "u128_dec_format")]
+    #[schemars(with = "String")]
+    pub amount: Balance,
+}
+
+impl Contract {
+    /// Sends the amount of the token to the receiver.
+    fn transfer(token: &Token, receiver_id: AccountId, amount: Balance) -> Promise {
+        match token {
+            Token::Near => Promise::new(receiver_id).transfer(amount),
+            Token::Ft(token_id) => ext_ft_core::ext(token_id.clone())
+                .with_attached_deposit(1)
+                .with_static_gas(GAS_FOR_FT_TRANSFER)
+                .ft_transfer(receiver_id, amount.into(), None),
+        }
+    }
+
+    /// Sends the amount of the token out of escrow to the receiver. If the transfer fails, the
+    /// deposits are put back into the escrow, or can be withdrawn by their funders if the escrow
+    /// can no longer be paid out. Payouts to a contributor are recorded on the
+    /// contribution once they succeed.
+    pub(crate) fn transfer_escrowed(
+        &mut self,
+        escrow: Escrow,
+        token: Token,
+        receiver_id: AccountId,
+        deposits: Vec<(AccountId, Balance)>,
+        contribution_id: Option<u32>,
+    ) -> Promise {
+        let amount: Balance = deposits.iter().map(|(_, amount)| amount).sum();
+        // NEAR bounties funded before the totals were tracked aren't counted in them.
+        let escrowed = self.escrowed.entry(token.clone()).or_default();
+        *escrowed = escrowed.saturating_sub(amount);
+        Self::transfer(&token, receiver_id.clone(), amount).then(
+            Self::ext(env::current_account_id())
+                .with_static_gas(GAS_FOR_RESOLVE_TRANSFER)
+                .on_escrow_transfer(
+                    escrow,
+                    token,
+                    receiver_id,
+                    deposits
+                        .into_iter()
+                        .map(|(account_id, amount)| (account_id, amount.into()))
+                        .collect(),
+                    contribution_id,
+                ),
+        )
+    }
+
+    /// Records a payment the contributor received on the contribution, which pays for it.
+    fn record_payment(
+        &mut self,
+        entity_id: &AccountId,
+        contributor_id: &AccountId,
+        contribution_id: u32,
+        payment: Payment,
+        storage_usage: &mut StorageUsage,
+    ) {
+        let key = (entity_id.clone(), contributor_id.clone());
+        // The contribution may have been removed while the transfer was in flight.
+        let Some(mut contribution) = self
+            .contributions
+            .get(&key)
+            .map(|contribution| Contribution::from(contribution.clone()))
+        else {
+            return;
+        };
+        let Some(detail) = contribution.details.get_mut(contribution_id as usize) else {
+            return;
+        };
+        detail.payments.push(payment);
+        self.contributions
+            .insert(key, VersionedContribution::Current(contribution));
+        self.charge_record_unchecked(
+            &StorageRecord::Contribution(entity_id.clone(), contributor_id.clone()),
+            contributor_id,
+            storage_usage,
+        );
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Moderator allows entities to pay with the fungible token.
+    pub fn add_token(&mut self, token_id: AccountId) {
+        self.assert_moderator();
+        self.tokens.insert(token_id.clone());
+        Events::AddToken { token_id }.emit();
+    }
+
+    /// Moderator stops accepting the fungible token. Tokens already in escrow are still paid out
+    /// or refunded.
+    pub fn remove_token(&mut self, token_id: AccountId) {
+        self.assert_moderator();
+        self.tokens.remove(&token_id);
+        Events::RemoveToken { token_id }.emit();
+    }
+
+    /// Resolves a transfer out of escrow. Successful payouts are recorded on the contribution,
+    /// failed transfers put the deposits back into the escrow if it can still be paid out and
+    /// the funder can pay for its storage. Other deposits can be withdrawn by their funders.
+    #[private]
+    pub fn on_escrow_transfer(
+        &mut self,
+        escrow: Escrow,
+        token: Token,
+        receiver_id: AccountId,
+        deposits: Vec<(AccountId, U128)>,
+        contribution_id: Option<u32>,
+    ) -> bool {
+        let mut storage_usage = env::storage_usage();
+        let amount: Balance = deposits.iter().map(|(_, amount)| amount.0).sum();
+        if matches!(env::promise_result(0), PromiseResult::Successful(_)) {
+            if let Some(contribution_id) = contribution_id {
+                self.record_payment(
+                    escrow.entity_id(),
+                    &receiver_id,
+                    contribution_id,
+                    Payment {
+                        token: token.clone(),
+                        amount,
+                    },
+                    &mut storage_usage,
+                );
+            }
+            Events::CompleteEscrowTransfer {
+                escrow,
+                token,
+                receiver_id,
+                amount,
+            }
+            .emit();
+            return true;
+        }
+        let is_open = self.is_escrow_open(&escrow);
+        let mut events = vec![Events::RevertEscrowTransfer {
+            escrow: escrow.clone(),
+            token: token.clone(),
+            receiver_id,
+            amount,
+        }];
+        for (account_id, amount) in deposits {
+            if is_open && self.storage_accounts.contains_key(&account_id) {
+                // The deposit goes back to its funder, who pays for the storage again.
+                self.add_to_bounty(
+                    &escrow,
+                    &account_id,
+                    token.clone(),
+                    amount.0,
+                    &mut storage_usage,
+                );
+                self.charge_record_unchecked(
+                    &StorageRecord::Escrow(escrow.clone()),
+                    &account_id,
+                    &mut storage_usage,
+                );
+            } else {
+                self.absorb_storage(&mut storage_usage, |this| {
+                    *this
+                        .claimable
+                        .entry((account_id.clone(), token.clone()))
+                        .or_default() += amount.0;
+                });
+                events.push(Events::AddClaimable {
+                    escrow: escrow.clone(),
+                    account_id,
+                    token: token.clone(),
+                    amount: amount.0,
+                });
+            }
+        }
+        Events::emit_batch(events);
+        false
+    }
+
+    /// Sends the caller the whole amount of the token it can claim. Requires exactly 1 yoctoNEAR
+    /// attached.
+    #[payable]
+    pub fn withdraw(&mut self, token: Token) -> Promise {
+        self.assert_not_paused(None);
+        assert_one_yocto();
+        let account_id = env::predecessor_account_id();
+        let amount = self
+            .claimable
+            .remove(&(account_id.clone(), token.clone()))
+            .unwrap_or_else(|| ContractError::NothingToWithdraw.panic());
+        Events::Withdraw {
+            account_id: account_id.clone(),
+            token: token.clone(),
+            amount,
+        }
+        .emit();
+        Self::transfer(&token, account_id.clone(), amount).then(
+            Self::ext(env::current_account_id())
+                .with_static_gas(GAS_FOR_RESOLVE_TRANSFER)
+                .on_withdraw(account_id, token, amount.into()),
+        )
+    }
+
+    /// Resolves a withdrawal. If the transfer failed, the amount can be claimed again.
+    #[private]
+    pub fn on_withdraw(&mut self, account_id: AccountId, token: Token, amount: U128) -> bool {
+        if matches!(env::promise_result(0), PromiseResult::Successful(_)) {
+            return true;
+        }
+        *self
+            .claimable
+            .entry((account_id.clone(), token.clone()))
+            .or_default() += amount.0;
+        Events::RevertWithdraw {
+            account_id,
+            token,
+            amount: amount.0,
+        }
+        .emit();
+        false
+    }
+
+    // Views
+
+    /// Get the fungible tokens entities can pay with.
+    pub fn get_tokens(&self, from_index: Option<U64>, limit: Option<U64>) -> Vec<AccountId> {
+        let (from_index, limit) = pagination(from_index, limit);
+        self.tokens
+            .iter()
+            .skip(from_index)
+            .take(limit)
+            .cloned()
+            .collect()
+    }
+
+    /// Get the amount of the token the account can withdraw.
+    pub fn get_claimable_balance(&self, account_id: AccountId, token: Token) -> U128 {
+        self.claimable
+            .get(&(account_id, token))
+            .copied()
+            .unwrap_or_default()
+            .into()
+    }
+
+    /// Get the total amount of the token held in escrow.
+    pub fn get_escrowed_balance(&self, token: Token) -> U128 {
+        self.escrowed
+            .get(&token)
+            .copied()
+            .unwrap_or_default()
+            .into()
+    }
+}
+
+#[near_bindgen]
+impl FungibleTokenReceiver for Contract {
+    /// Funds the escrow given in `msg`, a JSON `Escrow`, with whitelisted fungible tokens. The
+    /// sender needs the same permissions as for funding with NEAR. Any failure refunds the tokens.
+    fn ft_on_transfer(
+        &mut self,
+        sender_id: AccountId,
+        amount: U128,
+        msg: String,
+    ) -> PromiseOrValue<U128> {
+        self.assert_not_paused(Some(PauseCategory::Needs));
+        let mut storage_usage = env::storage_usage();
+        let token_id = env::predecessor_account_id();
+        require!(
+            self.tokens.contains(&token_id),
+            ContractError::TokenNotWhitelisted.to_string()
+        );
+        require!(amount.0 > 0, ContractError::ZeroDeposit.to_string());
+        let escrow: Escrow =
+            serde_json::from_str(&msg).unwrap_or_else(|_| ContractError::InvalidEscrow.panic());
+        self.assert_can_fund(&escrow, &sender_id);
+        let event = self.add_to_bounty(
+            &escrow,
+            &sender_id,
+            Token::Ft(token_id),
+            amount.0,
+            &mut storage_usage,
+        );
+        self.charge_record(
+            &StorageRecord::Escrow(escrow),
+            &sender_id,
+            &mut storage_usage,
+        );
+        event.emit();
+        PromiseOrValue::Value(U128(0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use near_contract_standards::fungible_token::receiver::FungibleTokenReceiver;
+    use near_sdk::json_types::U128;
+    use near_sdk::test_utils::{get_created_receipts, VMContextBuilder};
+    use near_sdk::{
+        serde_json, testing_env, AccountId, PromiseResult, RuntimeFeesConfig, VMConfig, ONE_NEAR,
+        ONE_YOCTO,
+    };
+
+    use crate::bounty::Escrow;
+    use crate::test_utils::*;
+    use crate::token::Token;
+    use crate::Contract;
+
+    fn token_id() -> AccountId {
+        "token.near".parse().unwrap()
+    }
+
+    fn contribution_escrow(contribution_id: u32) -> Escrow {
+        Escrow::Contribution {
+            entity_id: entity(),
+            contributor_id: alice(),
+            contribution_id,
+        }
+    }
+
+    /// Resolves the owner's one NEAR payout to alice with the given transfer result.
+    fn resolve(contract: &mut Contract, contribution_id: u32, result: PromiseResult) -> bool {
+        testing_env!(
+            VMContextBuilder::new()
+                .current_account_id(contract_id())
+                .predecessor_account_id(contract_id())
+                .block_timestamp(NOW)
+                .build(),
+            VMConfig::test(),
+            RuntimeFeesConfig::test(),
+            Default::default(),
+            vec![result],
+        );
+        contract.on_escrow_transfer(
+            contribution_escrow(contribution_id),
+            Token::Near,
+            alice(),
+            vec![(owner(), U128(ONE_NEAR))],
+            Some(contribution_id),
+        )
+    }
+
+    #[test]
+    fn completed_payout_is_recorded_on_the_contribution() {
+        let mut contract = setup_with_entity();
+        let contribution_id = add_contribution(&mut contract, &alice());
+        assert!(resolve(
+            &mut contract,
+            contribution_id,
+            PromiseResult::Successful(vec![])
+        ));
+        assert_eq!(event_names(), ["complete_escrow_transfer"]);
+        let contribution = contract.get_contribution(entity(), alice()).unwrap();
+        let payments = &contribution.details[contribution_id as usize].payments;
+        assert_eq!(payments.len(), 1);
+        assert_eq!(payments[0].amount, ONE_NEAR);
+    }
+
+    #[test]
+    fn failed_payout_goes_back_into_escrow() {
+        let mut contract = setup_with_entity();
+        let contribution_id = add_contribution(&mut contract, &alice());
+        assert!(!resolve(
+            &mut contract,
+            contribution_id,
+            PromiseResult::Failed
+        ));
+        assert_eq!(event_names(), ["revert_escrow_transfer"]);
+        assert_eq!(
+            contract.get_escrow(contribution_escrow(contribution_id)),
+            [(Token::Near, U128(ONE_NEAR))]
+        );
+        let contribution = contract.get_contribution(entity(), alice()).unwrap();
+        assert!(contribution.details[contribution_id as usize]
+            .payments
+            .is_empty());
+    }
+
+    #[test]
+    fn failed_payout_of_removed_entity_can_be_withdrawn() {
+        let mut contract = setup_with_entity();
+        let contribution_id = add_contribution(&mut contract, &alice());
+        set_caller(&owner());
+        assert!(contract.remove_entity(entity(), None));
+        assert!(!resolve(
+            &mut contract,
+            contribution_id,
+            PromiseResult::Failed
+        ));
+        assert_eq!(event_names(), ["revert_escrow_transfer", "add_claimable"]);
+        assert!(contract
+            .get_escrow(contribution_escrow(contribution_id))
+            .is_empty());
+        assert_eq!(
+            contract.get_claimable_balance(owner(), Token::Near),
+            U128(ONE_NEAR)
+        );
+
+        set_caller_with_deposit(&owner(), ONE_YOCTO);
+        contract.withdraw(Token::Near);
+        assert_eq!(event_names(), ["withdraw"]);
+        assert_eq!(get_created_receipts()[0].receiver_id, owner());
+        assert_eq!(
+            contract.get_claimable_balance(owner(), Token::Near),
+            U128(0)
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "ERR_NOTHING_TO_WITHDRAW")]
+    fn nothing_can_be_withdrawn_without_a_claimable_balance() {
+        let mut contract = setup_with_entity();
+        set_caller_with_deposit(&owner(), ONE_YOCTO);
+        contract.withdraw(Token::Near);
+    }
+
+    #[test]
+    fn whitelisted_token_funds_escrow() {
+        let mut contract = setup_with_entity();
+        let contribution_id = add_contribution(&mut contract, &alice());
+        set_caller(&moderator());
+        contract.add_token(token_id());
+        set_caller(&token_id());
+        contract.ft_on_transfer(
+            owner(),
+            U128(100),
+            serde_json::to_string(&contribution_escrow(contribution_id)).unwrap(),
+        );
+        assert_eq!(event_names(), ["fund_escrow"]);
+        assert_eq!(
+            contract.get_escrow(contribution_escrow(contribution_id)),
+            [(Token::Ft(token_id()), U128(100))]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "ERR_TOKEN_NOT_WHITELISTED")]
+    fn unknown_token_cannot_fund_escrow() {
+        let mut contract = setup_with_entity();
+        let contribution_id = add_contribution(&mut contract, &alice());
+        set_caller(&token_id());
+        contract.ft_on_transfer(
+            owner(),
+            U128(100),
+            serde_json::to_string(&contribution_escrow(contribution_id)).unwrap(),
+        );
+    }
+}
@@ -0,0 +1,146 @@
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::{Base58CryptoHash, U64};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, near_bindgen, require, CryptoHash, Gas, Promise, Timestamp};
+use schemars::JsonSchema;
+
+use crate::council::ModeratorAction;
+use crate::dec_serde::u64_dec_format;
+use crate::errors::ContractError;
+use crate::events::Events;
+use crate::{Contract, ContractExt};
+
+/// Time between staging an upgrade and being able to deploy it, unless changed: one day.
+pub(crate) const DEFAULT_UPGRADE_DELAY: Timestamp = 24 * 60 * 60 * 1_000_000_000;
+
+const MIGRATE_METHOD_NAME: &str = "migrate";
+const UPGRADE_GAS_LEFTOVER: Gas = Gas(5_000_000_000_000);
+
+/// Upgrade waiting for its delay to pass. The code itself is stored separately, so it isn't
+/// loaded with the rest of the state.
+#[derive(BorshSerialize, BorshDeserialize, Deserialize, Serialize, Clone, JsonSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct StagedUpgrade {
+    pub code_hash: Base58CryptoHash,
+    #[serde(with = "u64_dec_format")]
+    #[schemars(with = "String")]
+    pub staged_at: Timestamp,
+    /// The upgrade can't be deployed before this time.
+    #[serde(with = "u64_dec_format")]
+    #[schemars(with = "String")]
+    pub deploy_after: Timestamp,
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Deploys the staged upgrade and migrates the state. Only possible once the delay has
+    /// passed, and only if the staged code still has the given hash.
+    pub fn deploy_upgrade(&mut self, code_hash: Base58CryptoHash) -> Promise {
+        self.assert_moderator();
+        let staged_upgrade = self
+            .staged_upgrade
+            .clone()
+            .unwrap_or_else(|| ContractError::NoStagedUpgrade.panic());
+        require!(
+            env::block_timestamp() >= staged_upgrade.deploy_after,
+            ContractError::UpgradeNotReady.to_string()
+        );
+        let code = self
+            .staged_code
+            .get_mut()
+            .take()
+            .unwrap_or_else(|| ContractError::NoStagedUpgrade.panic());
+        let staged_hash: CryptoHash = staged_upgrade.code_hash.into();
+        let expected_hash: CryptoHash = code_hash.into();
+        require!(
+            staged_hash == expected_hash && env::sha256_array(&code) == expected_hash,
+            ContractError::UpgradeHashMismatch.to_string()
+        );
+        self.staged_upgrade = None;
+        Events::Upgrade { code_hash }.emit();
+        // Batched together to fail upgrade if migration fails.
+        Promise::new(env::current_account_id())
+            .deploy_contract(code)
+            .function_call(
+                MIGRATE_METHOD_NAME.to_string(),
+                vec![],
+                0,
+                env::prepaid_gas() - env::used_gas() - UPGRADE_GAS_LEFTOVER,
+            )
+    }
+
+    /// Drops the staged upgrade, for example if a problem was found while reviewing it.
+    pub fn cancel_upgrade(&mut self) {
+        self.assert_moderator();
+        let staged_upgrade = self
+            .staged_upgrade
+            .take()
+            .unwrap_or_else(|| ContractError::NoStagedUpgrade.panic());
+        self.staged_code.set(None);
+        Events::CancelUpgrade {
+            code_hash: staged_upgrade.code_hash,
+        }
+        .emit();
+    }
+
+    /// Sets the time between staging an upgrade and being able to deploy it, in nanoseconds.
+    /// Needs `threshold` moderator approvals.
+    pub fn set_upgrade_delay(&mut self, delay: U64) {
+        if !self.approve_moderator_action(ModeratorAction::SetUpgradeDelay {
+            delay: delay.into(),
+        }) {
+            return;
+        }
+        let old = std::mem::replace(&mut self.upgrade_delay, delay.into());
+        Events::SetUpgradeDelay {
+            old,
+            new: delay.into(),
+        }
+        .emit();
+    }
+
+    // Views
+
+    /// Get the upgrade waiting for its delay to pass.
+    pub fn get_staged_upgrade(&self) -> Option<StagedUpgrade> {
+        self.staged_upgrade.clone()
+    }
+
+    /// Get the time between staging an upgrade and being able to deploy it, in nanoseconds.
+    pub fn get_upgrade_delay(&self) -> U64 {
+        self.upgrade_delay.into()
+    }
+}
+
+/// Stages the code passed as the raw input for a later `deploy_upgrade`, replacing any staged
+/// upgrade. Needs `threshold` moderator approvals.
+#[no_mangle]
+pub fn stage_upgrade() {
+    // The hook reports panics through the host, which unit tests only mock per thread.
+    #[cfg(target_arch = "wasm32")]
+    env::setup_panic_hook();
+
+    let mut contract: Contract =
+        env::state_read().unwrap_or_else(|| ContractError::NotInitialized.panic());
+    let code = env::input().unwrap_or_default();
+    let code_hash = env::sha256_array(&code);
+    let is_approved = contract.approve_moderator_action(ModeratorAction::StageUpgrade {
+        code_hash: code_hash.into(),
+    });
+    if is_approved {
+        let staged_at = env::block_timestamp();
+        let staged_upgrade = StagedUpgrade {
+            code_hash: code_hash.into(),
+            staged_at,
+            deploy_after: staged_at + contract.upgrade_delay,
+        };
+        contract.staged_upgrade = Some(staged_upgrade.clone());
+        contract.staged_code.set(Some(code));
+        Events::StageUpgrade {
+            code_hash: staged_upgrade.code_hash,
+            deploy_after: staged_upgrade.deploy_after,
+        }
+        .emit();
+    }
+    env::state_write(&contract);
+}
@@ -0,0 +1,160 @@
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{near_bindgen, require};
+use schemars::JsonSchema;
+use std::collections::HashSet;
+
+use crate::contributor::ContributionType;
+use crate::errors::ContractError;
+use crate::events::Events;
+use crate::{Contract, ContractExt};
+
+/// Limits applied to user-supplied strings and sets. Lengths are in bytes.
+#[derive(BorshSerialize, BorshDeserialize, Deserialize, Serialize, Clone, JsonSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ValidationConfig {
+    /// Maximum length of an entity name.
+    pub max_name_length: u32,
+    /// Maximum length of a description of a need, request, invite or contribution.
+    pub max_description_length: u32,
+    /// Maximum length of a contributor resume.
+    pub max_resume_length: u32,
+    /// Maximum length of a single skill.
+    pub max_skill_length: u32,
+    /// Maximum number of skills of a contributor.
+    pub max_skills: u32,
+    /// Maximum length of the name of a `ContributionType::Other`.
+    pub max_contribution_type_length: u32,
+    /// Maximum number of contribution types of a contributor.
+    pub max_contribution_types: u32,
+}
+
+impl Default for ValidationConfig {
+    fn default() -> Self {
+        Self {
+            max_name_length: 100,
+            max_description_length: 420,
+            max_resume_length: 2000,
+            max_skill_length: 50,
+            max_skills: 50,
+            max_contribution_type_length: 50,
+            max_contribution_types: 10,
+        }
+    }
+}
+
+/// Single line values can't contain any control characters.
+fn is_valid_line(value: &str) -> bool {
+    !value.chars().any(char::is_control)
+}
+
+/// Multi line values can contain new lines and tabs, but no other control characters.
+fn is_valid_text(value: &str) -> bool {
+    !value
+        .chars()
+        .any(|c| c.is_control() && c != '\n' && c != '\t')
+}
+
+impl Contract {
+    /// Checks the name of an entity.
+    pub(crate) fn assert_valid_name(&self, name: &str) {
+        require!(
+            !name.trim().is_empty(),
+            ContractError::EmptyName.to_string()
+        );
+        require!(
+            name.len() <= self.validation_config.max_name_length as usize,
+            ContractError::NameTooLong.to_string()
+        );
+        require!(
+            is_valid_line(name),
+            ContractError::InvalidNameCharacters.to_string()
+        );
+    }
+
+    /// Checks a description of a need, request, invite or contribution.
+    pub(crate) fn assert_valid_description(&self, description: &str) {
+        require!(
+            description.len() <= self.validation_config.max_description_length as usize,
+            ContractError::DescriptionTooLong.to_string()
+        );
+        require!(
+            is_valid_text(description),
+            ContractError::InvalidDescriptionCharacters.to_string()
+        );
+    }
+
+    /// Checks the name of a custom contribution type.
+    pub(crate) fn assert_valid_contribution_type(&self, contribution_type: &ContributionType) {
+        let ContributionType::Other(name) = contribution_type else {
+            return;
+        };
+        require!(
+            name.len() <= self.validation_config.max_contribution_type_length as usize,
+            ContractError::ContributionTypeTooLong.to_string()
+        );
+        require!(
+            is_valid_line(name),
+            ContractError::InvalidContributionTypeCharacters.to_string()
+        );
+    }
+
+    /// Checks the details of a contributor profile.
+    pub(crate) fn assert_valid_contributor(
+        &self,
+        contribution_types: &HashSet<ContributionType>,
+        skills: &HashSet<String>,
+        resume: &str,
+    ) {
+        require!(
+            contribution_types.len() <= self.validation_config.max_contribution_types as usize,
+            ContractError::TooManyContributionTypes.to_string()
+        );
+        for contribution_type in contribution_types {
+            self.assert_valid_contribution_type(contribution_type);
+        }
+        require!(
+            skills.len() <= self.validation_config.max_skills as usize,
+            ContractError::TooManySkills.to_string()
+        );
+        for skill in skills {
+            require!(
+                !skill.trim().is_empty(),
+                ContractError::EmptySkill.to_string()
+            );
+            require!(
+                skill.len() <= self.validation_config.max_skill_length as usize,
+                ContractError::SkillTooLong.to_string()
+            );
+            require!(
+                is_valid_line(skill),
+                ContractError::InvalidSkillCharacters.to_string()
+            );
+        }
+        require!(
+            resume.len() <= self.validation_config.max_resume_length as usize,
+            ContractError::ResumeTooLong.to_string()
+        );
+        require!(
+            is_valid_text(resume),
+            ContractError::InvalidResumeCharacters.to_string()
+        );
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Moderator updates the validation limits.
+    pub fn set_validation_config(&mut self, config: ValidationConfig) {
+        self.assert_moderator();
+        let old = std::mem::replace(&mut self.validation_config, config.clone());
+        Events::SetValidationConfig { old, new: config }.emit();
+    }
+
+    // Views
+
+    /// Get the validation limits.
+    pub fn get_validation_config(&self) -> ValidationConfig {
+        self.validation_config.clone()
+    }
+}
@@ -0,0 +1,134 @@
+// Writes TypeScript definitions for the contract interface from its ABI.
+// Usage: node abi-to-ts.js <abi.json> <output.d.ts>
+import { readFileSync, writeFileSync } from "fs";
+
+const [abiPath, outputPath] = process.argv.slice(2);
+const abi = JSON.parse(readFileSync(abiPath, "utf8"));
+const { functions, root_schema } = abi.body;
+
+// Definitions that accept any value, like `Promise`, are inlined as `unknown`.
+const opaque = new Set(
+  Object.entries(root_schema.definitions ?? {})
+    .filter(([, schema]) => schema === true)
+    .map(([name]) => name)
+);
+
+// Only the first paragraph of the docs, the rest is usually Rust specific.
+const comment = (doc, indent = "") =>
+  doc
+    ? `${indent}/** ${doc
+        .trim()
+        .split(/\n\s*\n/)[0]
+        .replace(/\s*\n\s*/g, " ")} */\n`
+    : "";
+
+const isNullable = (schema) =>
+  [schema.type].flat().includes("null") ||
+  (schema.anyOf ?? []).some((s) => s.type === "null");
+
+const wrap = (type) => (type.includes(" | ") ? `(${type})` : type);
+
+const union = (types) => [...new Set(types)].join(" | ") || "never";
+
+const objectType = (schema, indent) => {
+  const required = new Set(schema.required ?? []);
+  const properties = Object.entries(schema.properties ?? {}).map(
+    ([name, property]) =>
+      `${comment(property.description, indent + "  ")}${indent}  ${name}${
+        required.has(name) ? "" : "?"
+      }: ${toTs(property, indent + "  ")};`
+  );
+  if (properties.length === 0) {
+    const values = schema.additionalProperties;
+    return `Record<string, ${values ? toTs(values, indent) : "unknown"}>`;
+  }
+  return `{\n${properties.join("\n")}\n${indent}}`;
+};
+
+function toTs(schema, indent = "") {
+  if (schema === true || schema === undefined) {
+    return "unknown";
+  }
+  if (schema.$ref) {
+    const name = schema.$ref.split("/").pop();
+    return opaque.has(name) ? "unknown" : name;
+  }
+  if (schema.anyOf || schema.oneOf) {
+    return union((schema.anyOf ?? schema.oneOf).map((s) => toTs(s, indent)));
+  }
+  if (schema.allOf) {
+    return schema.allOf.map((s) => wrap(toTs(s, indent))).join(" & ");
+  }
+  if (schema.enum) {
+    return union(schema.enum.map((value) => JSON.stringify(value)));
+  }
+  if (schema.const !== undefined) {
+    return JSON.stringify(schema.const);
+  }
+  if (Array.isArray(schema.type)) {
+    return union(schema.type.map((type) => toTs({ ...schema, type }, indent)));
+  }
+  switch (schema.type) {
+    case "string":
+      return "string";
+    case "integer":
+    case "number":
+      return "number";
+    case "boolean":
+      return "boolean";
+    case "null":
+      return "null";
+    case "array":
+      return Array.isArray(schema.items)
+        ? `[${schema.items.map((s) => toTs(s, indent)).join(", ")}]`
+        : `${wrap(toTs(schema.items, indent))}[]`;
+    case "object":
+      return objectType(schema, indent);
+    default:
+      return "unknown";
+  }
+}
+
+const definitions = Object.entries(root_schema.definitions ?? {})
+  .filter(([name]) => !opaque.has(name))
+  .sort(([a], [b]) => a.localeCompare(b))
+  .map(
+    ([name, schema]) =>
+      `${comment(schema.description)}export type ${name} = ${toTs(schema)};`
+  );
+
+const methods = (kind) =>
+  functions
+    .filter((f) => f.kind === kind && !(f.modifiers ?? []).includes("init"))
+    .sort((a, b) => a.name.localeCompare(b.name))
+    .map((f) => {
+      const args = (f.params?.args ?? []).map(
+        (arg) =>
+          `${arg.name}${isNullable(arg.type_schema) ? "?" : ""}: ${toTs(
+            arg.type_schema,
+            "    "
+          )}`
+      );
+      const result = f.result ? toTs(f.result.type_schema, "    ") : "void";
+      return `${comment(f.doc, "  ")}  ${f.name}: {\n    args: ${
+        args.length ? `{ ${args.join("; ")} }` : "{}"
+      };\n    result: ${result};\n  };`;
+    });
+
+writeFileSync(
+  outputPath,
+  `// Generated from the contract ABI by abi.sh, do not edit.
+
+${definitions.join("\n\n")}
+
+/** View methods of the contract, callable with \`Near.view\`. */
+export interface ViewMethods {
+${methods("view").join("\n")}
+}
+
+/** Change methods of the contract, callable with \`Near.call\`. */
+export interface CallMethods {
+${methods("call").join("\n")}
+}
+`
+);
@@ -0,0 +1,922 @@
+// Generated from the contract ABI by abi.sh, do not edit.
+
+/** Account identifier. This is the human readable utf8 string which is used internally to index accounts on the network and their respective state. */
+export type AccountId = string;
+
+/** Progress of a migration that runs across several `migrate_state` calls. */
+export type BatchMigration = {
+  /** Number of records of the step already migrated. */
+  from_index: number;
+  /** Step being run. */
+  step: MigrationStep;
+};
+
+/** Relation between entity and contributor. Managed by source account. */
+export type Contribution = {
+  /** Every contribution made to the entity, ongoing or finished. The position of a contribution is its ID, contributions are never removed from the list. */
+  details: ContributionDetail[];
+  /** Set of permissions this contributor has for the entity. */
+  permissions: Permission[];
+};
+
+/** The story/description of a contribution to an entity. */
+export type ContributionDetail = {
+  /** The type of this contribution. */
+  contribution_type: ContributionType;
+  /** Details about this contribution. */
+  description: string;
+  /** The end date of the contribution (if not ongoing). */
+  end_date?: string | null;
+  /** Steps of the contribution, in order. The position of a milestone is its ID. */
+  milestones: Milestone[];
+  /** The CID of the need this is associated with (if any). */
+  need?: string | null;
+  /** Payments the contributor received for this contribution, in the order they were made. */
+  payments: Payment[];
+  /** The start date of the contribution. */
+  start_date: string;
+  /** Whether the entity confirmed this contribution. */
+  verification: Verification;
+};
+
+/** A invite to contribute to a entity. */
+export type ContributionInvite = {
+  /** The type of invite this is. */
+  contribution_type: ContributionType;
+  /** The details of the invite. */
+  description: string;
+  /** The set of permissions a contributor will get if they accept the invite. */
+  permissions: Permission[];
+  /** The start date of the contribution after accepting the invite. */
+  start_date: string;
+};
+
+/** A need that a entity may have. */
+export type ContributionNeed = {
+  /** Whether this need is currently active. */
+  active: boolean;
+  /** The type of need this is. */
+  contribution_type: ContributionType;
+  /** The details of the need. */
+  description: string;
+};
+
+/** Request to contribute. */
+export type ContributionRequest = {
+  /** The type of request this is. */
+  contribution_type: ContributionType;
+  /** The account that sent the request. */
+  contributor_id: AccountId;
+  /** The details of the request. */
+  description: string;
+  /** The entity the request was sent to. */
+  entity_id: AccountId;
+  /** The CID of the need this is associated with (if any). */
+  need?: string | null;
+};
+
+/** Types of contributions a contributor is looking to do. */
+export type ContributionType = "Development" | "Investment" | "Marketing" | "Legal" | "Founding" | {
+  Other: string;
+};
+
+/** Details of a contritbutor. */
+export type Contributor = {
+  /** The types of contributions the contributor is offering. */
+  contribution_types: ContributionType[];
+  /** Whether the contributor is looking for work. */
+  looking_for_work: boolean;
+  /** The resume of the contributor. */
+  resume: string;
+  /** The skills the contributor has. */
+  skills: string[];
+};
+
+/** Endorsement of a contributor by another account. */
+export type Endorsement = {
+  /** When the endorsement was given. */
+  created_at: string;
+  /** What the endorser has to say about the target (if anything). */
+  text?: string | null;
+};
+
+/** What an endorsement is for. */
+export type EndorsementTarget = {
+  Skill: {
+    contributor_id: AccountId;
+    skill: string;
+  };
+} | {
+  Contribution: {
+    contribution_id: number;
+    contributor_id: AccountId;
+    entity_id: AccountId;
+  };
+};
+
+/** Entity is something that is beyond a single person. Something that has a start and potentially an end. Note, that all the basic information like name, description and social information is stored in the `socialdb`. */
+export type Entity = {
+  /** The end date of the entity. (optional) */
+  end_date?: string | null;
+  /** The type of the entity. */
+  kind: EntityKind;
+  /** Name of the entity. */
+  name: string;
+  /** The start date of the entity. */
+  start_date: string;
+  /** Status of the entity. */
+  status: EntityStatus;
+};
+
+/** An entity can take different shapes, and currently we can categorize them in these types. */
+export type EntityKind = "Project" | "Organization" | "DAO";
+
+/** An entity can be in different states because it can potentially have an end (through different ways - legal issues, no funding...). This is represented by the EntityStatus. Only active entities can post needs, send invites and receive requests. */
+export type EntityStatus = "Active" | "Flagged" | "Closed" | "Removing";
+
+/** Entry of the error catalogue. */
+export type ErrorInfo = {
+  code: number;
+  message: string;
+  name: string;
+};
+
+/** What funds in escrow are held for. */
+export type Escrow = {
+  Need: {
+    cid: string;
+    entity_id: AccountId;
+  };
+} | {
+  Contribution: {
+    contribution_id: number;
+    contributor_id: AccountId;
+    entity_id: AccountId;
+  };
+};
+
+/** Steps of a batch migration, in the order they run. */
+export type MigrationStep = "Contributions" | "Requests" | "Invites" | "Needs" | "Owners";
+
+/** Step of a contribution, optionally paid from the contribution's escrow when approved. */
+export type Milestone = {
+  /** What has to be delivered. */
+  description: string;
+  /** When the milestone is due. */
+  due_date: string;
+  /** Paid to the contributor from the contribution's escrow when the milestone is approved. */
+  payment?: Payment | null;
+  /** Progress of the milestone. */
+  status: MilestoneStatus;
+};
+
+/** Progress of a milestone. */
+export type MilestoneStatus = "Pending" | "Submitted" | "Approved" | "Rejected";
+
+/** Sensitive actions that need the approval of `threshold` moderators. */
+export type ModeratorAction = {
+  action: "stage_upgrade";
+  code_hash: string;
+} | {
+  action: "set_upgrade_delay";
+  delay: string;
+} | {
+  action: "set_moderator";
+  moderator_id: AccountId;
+  old_moderator_id?: AccountId | null;
+} | {
+  action: "add_moderator";
+  moderator_id: AccountId;
+} | {
+  action: "remove_moderator";
+  moderator_id: AccountId;
+} | {
+  action: "set_moderator_threshold";
+  threshold?: number | null;
+} | {
+  account_id: AccountId;
+  action: "admin_add_entity";
+  founder_id: AccountId;
+  kind: EntityKind;
+  name: string;
+  start_date: string;
+};
+
+/** Sensitive action waiting for the approval of more moderators. */
+export type ModeratorProposal = {
+  action: ModeratorAction;
+  /** Moderators that approved the action. */
+  approvals: AccountId[];
+};
+
+/** Categories of methods that can be paused separately. */
+export type PauseCategory = "EntityCreation" | "Requests" | "Invites" | "Needs" | "Registration" | "Endorsements" | "Reviews";
+
+/** Which methods are currently paused. */
+export type PauseState = {
+  /** Every method except the moderators' is paused. */
+  all: boolean;
+  /** Paused categories of methods. */
+  categories: PauseCategory[];
+};
+
+/** Payment a contributor received for a contribution. */
+export type Payment = {
+  /** The amount that was paid. */
+  amount: string;
+  /** The token that was paid. */
+  token: Token;
+};
+
+/** Permissions table for interaction between a contributor and an entity. Roles are ordered as Owner > Admin > Manager > Member, and each role has all the capabilities of the roles below it. */
+export type Permission = "Admin" | "Owner" | "Manager" | "Member";
+
+export type PromiseOrValueString = string;
+
+/** Aggregate of the ratings an account received. */
+export type Rating = {
+  /** Number of reviews. */
+  count: number;
+  /** Sum of the ratings of the reviews. */
+  total: number;
+};
+
+/** Feedback on a finished contribution. */
+export type Review = {
+  /** When the review was first written. It can be edited for a week after that. */
+  created_at: string;
+  /** Rating from one to five. */
+  rating: number;
+  /** The account that wrote the review. */
+  reviewer_id: AccountId;
+  /** Short review. */
+  text: string;
+};
+
+/** Who wrote a review of a contribution. */
+export type ReviewSide = "Entity" | "Contributor";
+
+/** Upgrade waiting for its delay to pass. The code itself is stored separately, so it isn't loaded with the rest of the state. */
+export type StagedUpgrade = {
+  code_hash: string;
+  /** The upgrade can't be deployed before this time. */
+  deploy_after: string;
+  staged_at: string;
+};
+
+export type StorageBalance = {
+  available: string;
+  total: string;
+};
+
+export type StorageBalanceBounds = {
+  max?: string | null;
+  min: string;
+};
+
+/** Asset that entities pay contributors with. */
+export type Token = "Near" | {
+  Ft: AccountId;
+};
+
+/** Limits applied to user-supplied strings and sets. Lengths are in bytes. */
+export type ValidationConfig = {
+  /** Maximum length of the name of a `ContributionType::Other`. */
+  max_contribution_type_length: number;
+  /** Maximum number of contribution types of a contributor. */
+  max_contribution_types: number;
+  /** Maximum length of a description of a need, request, invite or contribution. */
+  max_description_length: number;
+  /** Maximum length of an entity name. */
+  max_name_length: number;
+  /** Maximum length of a contributor resume. */
+  max_resume_length: number;
+  /** Maximum length of a single skill. */
+  max_skill_length: number;
+  /** Maximum number of skills of a contributor. */
+  max_skills: number;
+};
+
+/** Whether the entity confirmed a contribution. */
+export type Verification = "Verified" | "Unverified" | "Disputed";
+
+/** View methods of the contract, callable with `Near.view`. */
+export interface ViewMethods {
+  /** Checks whether the contributor with the provided contributor ID already proposed to the need with the given entity ID and CID. */
+  check_if_need_proposed: {
+    args: { entity_id: AccountId; contributor_id: AccountId; cid: string };
+    result: boolean;
+  };
+  /** Check if account is registered as contributor. */
+  check_is_contributor: {
+    args: { account_id: AccountId };
+    result: boolean;
+  };
+  /** Check if account ID is an entity. */
+  check_is_entity: {
+    args: { account_id: AccountId };
+    result: boolean;
+  };
+  /** Check if given account ID is manager or higher for given entity. */
+  check_is_manager_or_higher: {
+    args: { entity_id: AccountId; account_id: AccountId };
+    result: boolean;
+  };
+  /** Check if given account ID is a member of the moderator council. */
+  check_is_moderator: {
+    args: { account_id: AccountId };
+    result: boolean;
+  };
+  /** Get contribution needs this account can manage. */
+  get_admin_contribution_needs: {
+    args: { account_id: AccountId; from_index?: string | null; limit?: string | null };
+    result: [AccountId, string][];
+  };
+  /** Get the IDs of the contribution requests this account can manage. */
+  get_admin_contribution_requests: {
+    args: { account_id: AccountId; from_index?: string | null; limit?: string | null };
+    result: string[];
+  };
+  /** List out entities that account ID is admin (or higher) for. */
+  get_admin_entities: {
+    args: { account_id: AccountId; from_index?: string | null; limit?: string | null };
+    result: AccountId[];
+  };
+  /** Get the amount of the token the account can withdraw. */
+  get_claimable_balance: {
+    args: { account_id: AccountId; token: Token };
+    result: string;
+  };
+  /** Get contribution details. */
+  get_contribution: {
+    args: { entity_id: AccountId; contributor_id: AccountId };
+    result: Contribution | null;
+  };
+  /** Get contribution need details. */
+  get_contribution_need: {
+    args: { account_id: AccountId; cid: string };
+    result: ContributionNeed | null;
+  };
+  /** Get contribution needs. By default returns the first page of them. */
+  get_contribution_needs: {
+    args: { from_index?: string | null; limit?: string | null };
+    result: [AccountId, string][];
+  };
+  /** Get the total number of contribution needs. */
+  get_contribution_needs_count: {
+    args: {};
+    result: string;
+  };
+  /** Get contribution request details. */
+  get_contribution_request: {
+    args: { request_id: string };
+    result: ContributionRequest | null;
+  };
+  /** Get all contribution types. */
+  get_contribution_types: {
+    args: {};
+    result: ContributionType[];
+  };
+  /** Get contributor details. */
+  get_contributor: {
+    args: { account_id: AccountId };
+    result: Contributor | null;
+  };
+  /** Get the IDs of the requests this contributor sent. */
+  get_contributor_contribution_requests: {
+    args: { account_id: AccountId; from_index?: string | null; limit?: string | null };
+    result: string[];
+  };
+  /** Get the number of requests this contributor sent. */
+  get_contributor_contribution_requests_count: {
+    args: { account_id: AccountId };
+    result: string;
+  };
+  /** Get the entities a single contributor contributes to. */
+  get_contributor_contributions: {
+    args: { account_id: AccountId; from_index?: string | null; limit?: string | null };
+    result: AccountId[];
+  };
+  /** Get the number of contributions for a single contributor. */
+  get_contributor_contributions_count: {
+    args: { account_id: AccountId };
+    result: string;
+  };
+  /** Get the endorsements a contributor received, as target and endorser. */
+  get_contributor_endorsements: {
+    args: { account_id: AccountId; from_index?: string | null; limit?: string | null };
+    result: [EndorsementTarget, AccountId][];
+  };
+  /** Get the number of endorsements a contributor received. */
+  get_contributor_endorsements_count: {
+    args: { account_id: AccountId };
+    result: string;
+  };
+  /** List invites sent to contributor with given account ID. */
+  get_contributor_invites: {
+    args: { account_id: AccountId; from_index?: string | null; limit?: string | null };
+    result: AccountId[];
+  };
+  /** Get the number of invites sent to contributor with given account ID. */
+  get_contributor_invites_count: {
+    args: { account_id: AccountId };
+    result: string;
+  };
+  /** Get the aggregate of the ratings entities gave to the contributor. */
+  get_contributor_rating: {
+    args: { account_id: AccountId };
+    result: Rating;
+  };
+  /** Get contributor account IDs. By default returns the first page of them. */
+  get_contributors: {
+    args: { from_index?: string | null; limit?: string | null };
+    result: AccountId[];
+  };
+  /** Get the total number of contributors. */
+  get_contributors_count: {
+    args: {};
+    result: string;
+  };
+  /** Get the endorsement of the target by the endorser. */
+  get_endorsement: {
+    args: { target: EndorsementTarget; endorser_id: AccountId };
+    result: Endorsement | null;
+  };
+  /** Get the endorsements of a skill or a contribution, with their endorsers. */
+  get_endorsements: {
+    args: { target: EndorsementTarget; from_index?: string | null; limit?: string | null };
+    result: [AccountId, Endorsement][];
+  };
+  /** Get the number of endorsements of a skill or a contribution. */
+  get_endorsements_count: {
+    args: { target: EndorsementTarget };
+    result: string;
+  };
+  /** List out entities. By default list the first page of them. */
+  get_entities: {
+    args: { from_index?: string | null; limit?: string | null };
+    result: AccountId[];
+  };
+  /** Get the total number of entities. */
+  get_entities_count: {
+    args: {};
+    result: string;
+  };
+  /** List single entity details. */
+  get_entity: {
+    args: { account_id: AccountId };
+    result: Entity;
+  };
+  /** Get contribution needs of entity. */
+  get_entity_contribution_needs: {
+    args: { account_id: AccountId; from_index?: string | null; limit?: string | null };
+    result: string[];
+  };
+  /** Get the number of contribution needs of entity. */
+  get_entity_contribution_needs_count: {
+    args: { account_id: AccountId };
+    result: string;
+  };
+  /** Get the IDs of the requests sent to this entity. */
+  get_entity_contribution_requests: {
+    args: { account_id: AccountId; from_index?: string | null; limit?: string | null };
+    result: string[];
+  };
+  /** Get the number of requests for this entity. */
+  get_entity_contribution_requests_count: {
+    args: { account_id: AccountId };
+    result: string;
+  };
+  /** Get the contributors of this entity with at least one verified contribution. Contributors with only unverified claims are listed by `get_entity_unverified_contributions`. */
+  get_entity_contributions: {
+    args: { account_id: AccountId; from_index?: string | null; limit?: string | null };
+    result: AccountId[];
+  };
+  /** Get the number of contributors of this entity with at least one verified contribution. */
+  get_entity_contributions_count: {
+    args: { account_id: AccountId };
+    result: string;
+  };
+  /** List invites sent by entity with given account ID. */
+  get_entity_invites: {
+    args: { account_id: AccountId; from_index?: string | null; limit?: string | null };
+    result: AccountId[];
+  };
+  /** Get the number of invites sent by entity with given account ID. */
+  get_entity_invites_count: {
+    args: { account_id: AccountId };
+    result: string;
+  };
+  /** Get the aggregate of the ratings contributors gave to the entity. */
+  get_entity_rating: {
+    args: { account_id: AccountId };
+    result: Rating;
+  };
+  /** Get the claimed contributions of this entity waiting for verification, as contributor and contribution ID. */
+  get_entity_unverified_contributions: {
+    args: { account_id: AccountId; from_index?: string | null; limit?: string | null };
+    result: [AccountId, number][];
+  };
+  /** Get all the errors the contract can fail with. */
+  get_errors: {
+    args: {};
+    result: ErrorInfo[];
+  };
+  /** Get the amount held in escrow, per token. */
+  get_escrow: {
+    args: { escrow: Escrow };
+    result: [Token, string][];
+  };
+  /** Get the amount each funder deposited in escrow, per token. */
+  get_escrow_deposits: {
+    args: { escrow: Escrow };
+    result: [Token, AccountId, string][];
+  };
+  /** Get the total amount of the token held in escrow. */
+  get_escrowed_balance: {
+    args: { token: Token };
+    result: string;
+  };
+  /** List entity founders. */
+  get_founders: {
+    args: { account_id: AccountId; from_index?: string | null; limit?: string | null };
+    result: AccountId[];
+  };
+  /** Get invite details for entity and contributor with given IDs. */
+  get_invite: {
+    args: { entity_id: AccountId; contributor_id: AccountId };
+    result: ContributionInvite | null;
+  };
+  /** List out entities that account ID is manager (or higher) for. */
+  get_managed_entities: {
+    args: { account_id: AccountId; from_index?: string | null; limit?: string | null };
+    result: AccountId[];
+  };
+  /** Get the sensitive actions waiting for more moderator approvals. */
+  get_moderator_proposals: {
+    args: { from_index?: string | null; limit?: string | null };
+    result: [string, ModeratorProposal][];
+  };
+  /** Get the number of moderator approvals sensitive actions need. */
+  get_moderator_threshold: {
+    args: {};
+    result: number;
+  };
+  /** Get the members of the moderator council. */
+  get_moderators: {
+    args: { from_index?: string | null; limit?: string | null };
+    result: AccountId[];
+  };
+  /** Get the IDs of the requests to contribute to a specific need. */
+  get_need_contribution_requests: {
+    args: { account_id: AccountId; cid: string; from_index?: string | null; limit?: string | null };
+    result: string[];
+  };
+  /** Get the contributors for a specific need. */
+  get_need_contributions: {
+    args: { account_id: AccountId; cid: string; from_index?: string | null; limit?: string | null };
+    result: AccountId[];
+  };
+  /** Get which methods are currently paused. */
+  get_pause_state: {
+    args: {};
+    result: PauseState;
+  };
+  /** Get the progress of the pending batch migration. */
+  get_pending_migration: {
+    args: {};
+    result: BatchMigration | null;
+  };
+  /** Get the review of a contribution written by the given side. */
+  get_review: {
+    args: { entity_id: AccountId; contributor_id: AccountId; contribution_id: number; side: ReviewSide };
+    result: Review | null;
+  };
+  /** Get the highest role the account has in the entity (if any). */
+  get_role: {
+    args: { entity_id: AccountId; account_id: AccountId };
+    result: Permission | null;
+  };
+  /** Get the upgrade waiting for its delay to pass. */
+  get_staged_upgrade: {
+    args: {};
+    result: StagedUpgrade | null;
+  };
+  /** Get the version of the contract state layout. */
+  get_state_version: {
+    args: {};
+    result: number;
+  };
+  /** Get the fungible tokens entities can pay with. */
+  get_tokens: {
+    args: { from_index?: string | null; limit?: string | null };
+    result: AccountId[];
+  };
+  /** Get the time between staging an upgrade and being able to deploy it, in nanoseconds. */
+  get_upgrade_delay: {
+    args: {};
+    result: string;
+  };
+  /** Get the validation limits. */
+  get_validation_config: {
+    args: {};
+    result: ValidationConfig;
+  };
+  storage_balance_bounds: {
+    args: {};
+    result: StorageBalanceBounds;
+  };
+  storage_balance_of: {
+    args: { account_id: AccountId };
+    result: StorageBalance | null;
+  };
+}
+
+/** Change methods of the contract, callable with `Near.call`. */
+export interface CallMethods {
+  /** Accept a contribution invite from an entity with the given account ID. */
+  accept_invite: {
+    args: { account_id: AccountId };
+    result: void;
+  };
+  /** Add new entity and given user as founding contributor. */
+  add_entity: {
+    args: { account_id: AccountId; name: string; kind: EntityKind; start_date: string };
+    result: void;
+  };
+  /** Entity manager (or higher) adds a milestone to an ongoing contribution. The milestone starts pending whatever status is given. Returns the ID of the milestone. */
+  add_milestone: {
+    args: { entity_id: AccountId; contributor_id: AccountId; contribution_id: number; milestone: Milestone };
+    result: number;
+  };
+  /** Adds a moderator to the council. Needs `threshold` approvals. */
+  add_moderator: {
+    args: { moderator_id: AccountId };
+    result: void;
+  };
+  /** Moderator allows entities to pay with the fungible token. */
+  add_token: {
+    args: { token_id: AccountId };
+    result: void;
+  };
+  /** Add new entity and user as founding contributor. Needs `threshold` moderator approvals. Existing entities can't be added again. */
+  admin_add_entity: {
+    args: { account_id: AccountId; founder_id: AccountId; name: string; kind: EntityKind; start_date: string };
+    result: void;
+  };
+  /** Entity manager (or higher) approves a contribution request, optionally splitting the contribution into milestones. Milestones start pending whatever status is given. */
+  approve_contribution: {
+    args: { request_id: string; description?: string | null; start_date?: string | null; milestones?: Milestone[] | null };
+    result: void;
+  };
+  /** Entity manager (or higher) approves a submitted milestone. Its payment, if any, is released from the contribution's escrow to the contributor. */
+  approve_milestone: {
+    args: { entity_id: AccountId; contributor_id: AccountId; contribution_id: number; milestone_id: number };
+    result: void;
+  };
+  /** Entity manager (or higher) refunds the escrow of a contribution to its funders. */
+  cancel_contribution_escrow: {
+    args: { entity_id: AccountId; contributor_id: AccountId; contribution_id: number };
+    result: void;
+  };
+  /** Entity admin (or higher) cancels an invite the entity sent that wasn't answered yet. */
+  cancel_invite: {
+    args: { entity_id: AccountId; contributor_id: AccountId };
+    result: void;
+  };
+  /** Drops the staged upgrade, for example if a problem was found while reviewing it. */
+  cancel_upgrade: {
+    args: {};
+    result: void;
+  };
+  /** Contributor claims a past contribution to the entity. The claim is unverified until the entity's admins verify it. Returns the ID of the contribution. */
+  claim_contribution: {
+    args: { entity_id: AccountId; description: string; contribution_type: ContributionType; start_date: string; end_date: string };
+    result: number;
+  };
+  /** Entity owner (or moderator) closes the entity with the given end date. */
+  close_entity: {
+    args: { account_id: AccountId; end_date: string };
+    result: void;
+  };
+  /** Deploys the staged upgrade and migrates the state. Only possible once the delay has passed, and only if the staged code still has the given hash. */
+  deploy_upgrade: {
+    args: { code_hash: string };
+    result: unknown;
+  };
+  /** Entity admin (or higher) disputes a contribution claimed by the contributor. The claim stays on record as disputed and can still be verified later. */
+  dispute_contribution: {
+    args: { entity_id: AccountId; contributor_id: AccountId; contribution_id: number; reason: string };
+    result: void;
+  };
+  /** Edit contributor profile. */
+  edit_contributor: {
+    args: { contributor: Contributor };
+    result: void;
+  };
+  /** Endorse a skill or a contribution of another contributor, with an optional text. Each account can endorse a target once. */
+  endorse: {
+    args: { target: EndorsementTarget; text?: string | null };
+    result: void;
+  };
+  /** Entity manager (or higher), or the contributor themselves, marks the contribution as finished/completed. Other ongoing contributions of the contributor to the entity are not affected. When the entity finishes the contribution, the escrow of the contribution and the bounty of its need are paid to the contributor, except for the payments of milestones that weren't approved, which are refunded to the funders. */
+  finish_contribution: {
+    args: { entity_id: AccountId; contributor_id: AccountId; contribution_id: number; end_date: string };
+    result: void;
+  };
+  /** Moderator flags the entity for review. */
+  flag_entity: {
+    args: { account_id: AccountId; reason: string };
+    result: void;
+  };
+  /** Funds the escrow given in `msg`, a JSON `Escrow`, with whitelisted fungible tokens. The sender needs the same permissions as for funding with NEAR. Any failure refunds the tokens. */
+  ft_on_transfer: {
+    args: { sender_id: AccountId; amount: string; msg: string };
+    result: PromiseOrValueString;
+  };
+  /** Attach the deposit to the escrow of an active contribution need or an ongoing contribution. Need bounties are paid to the contributor when an entity manager finishes a contribution for the need, and refunded to the funders if the need is deactivated or the entity removed. Contribution escrows are paid when an entity manager finishes the contribution, and refunded if the entity cancels them or is removed. Fungible tokens are escrowed through `ft_transfer_call` instead. */
+  fund_escrow: {
+    args: { escrow: Escrow };
+    result: void;
+  };
+  /** Entity admin (or higher) grants a permission to a contributor of the entity. */
+  grant_permission: {
+    args: { entity_id: AccountId; contributor_id: AccountId; permission: Permission };
+    result: void;
+  };
+  /** Invite a user as a contributor to an entity. */
+  invite_contributor: {
+    args: { entity_id: AccountId; contributor_id: AccountId; description: string; contribution_type: ContributionType; start_date: string; permissions: Permission[] };
+    result: void;
+  };
+  /** Contributor leaves the entity: their ongoing contributions end at the given date, escrows of those contributions are refunded to their funders and they lose their permissions. Their past contributions stay on record. Owners have to transfer the ownership first, and the last admin of an entity without an owner can't leave. */
+  leave_entity: {
+    args: { entity_id: AccountId; end_date: string };
+    result: void;
+  };
+  /** Moderator runs at most `limit` records of the pending batch migration. Returns true once there is nothing left to migrate. Other writes are rejected until then. */
+  migrate_state: {
+    args: { limit?: string | null };
+    result: boolean;
+  };
+  /** Resolves a transfer out of escrow. Successful payouts are recorded on the contribution, failed transfers put the deposits back into the escrow if it can still be paid out and the funder can pay for its storage. Other deposits can be withdrawn by their funders. */
+  on_escrow_transfer: {
+    args: { escrow: Escrow; token: Token; receiver_id: AccountId; deposits: [AccountId, string][]; contribution_id?: number | null };
+    result: boolean;
+  };
+  /** Resolves a withdrawal. If the transfer failed, the amount can be claimed again. */
+  on_withdraw: {
+    args: { account_id: AccountId; token: Token; amount: string };
+    result: boolean;
+  };
+  /** Moderator pauses the given categories of methods, or the whole contract if none are given. */
+  pause: {
+    args: { categories?: PauseCategory[] | null };
+    result: void;
+  };
+  /** Create a contribution need. An attached deposit is held as the bounty of the need. */
+  post_contribution_need: {
+    args: { entity_id: AccountId; description: string; contribution_type: ContributionType };
+    result: void;
+  };
+  /** Entity owner (or moderator) reactivates a closed entity. */
+  reactivate_entity: {
+    args: { account_id: AccountId };
+    result: void;
+  };
+  /** Register as a contributor. */
+  register: {
+    args: { contribution_types: ContributionType[]; skills: string[]; resume: string };
+    result: void;
+  };
+  /** Entity manager (or higher) rejects a contribution request. */
+  reject_contribution: {
+    args: { request_id: string };
+    result: void;
+  };
+  /** Reject a contribution inivte from an entity with the given account ID. */
+  reject_invite: {
+    args: { account_id: AccountId };
+    result: void;
+  };
+  /** Entity manager (or higher) rejects a submitted milestone. The contributor can submit it again. */
+  reject_milestone: {
+    args: { entity_id: AccountId; contributor_id: AccountId; contribution_id: number; milestone_id: number; reason: string };
+    result: void;
+  };
+  /** Entity owner (or moderator) removes the entity together with its needs, invites, requests and contributions, and the reviews and endorsements of those contributions. At most `limit` records are removed per call, so large entities are removed by calling this repeatedly. The owner's contribution and the entity itself are removed last. Returns whether the entity is fully removed. */
+  remove_entity: {
+    args: { account_id: AccountId; limit?: string | null };
+    result: boolean;
+  };
+  /** Removes a moderator from the council. The council must keep at least one moderator, and enough moderators to reach the threshold. Needs `threshold` approvals. */
+  remove_moderator: {
+    args: { moderator_id: AccountId };
+    result: void;
+  };
+  /** Moderator stops accepting the fungible token. Tokens already in escrow are still paid out or refunded. */
+  remove_token: {
+    args: { token_id: AccountId };
+    result: void;
+  };
+  /** User requests to contribute to a given entity. A contributor can have several requests to the same entity, but only one per need. Returns the ID of the request. */
+  request_contribution: {
+    args: { entity_id: AccountId; description: string; contribution_type: ContributionType; need?: string | null };
+    result: string;
+  };
+  /** Review a verified, finished contribution with a rating from one to five and a short text. The contributor reviews the entity, entity admins (or higher) review the contributor. Reviews can be edited for a week after they are written. */
+  review_contribution: {
+    args: { entity_id: AccountId; contributor_id: AccountId; contribution_id: number; rating: number; text: string };
+    result: void;
+  };
+  /** Revoke an endorsement given before. */
+  revoke_endorsement: {
+    args: { target: EndorsementTarget };
+    result: void;
+  };
+  /** Entity admin (or higher) revokes a permission from a contributor of the entity. The last admin of an entity can't be removed. */
+  revoke_permission: {
+    args: { entity_id: AccountId; contributor_id: AccountId; permission: Permission };
+    result: void;
+  };
+  /** Update a contribution need. Deactivating the need refunds its bounty to the funders. */
+  set_contribution_need: {
+    args: { entity_id: AccountId; cid: string; need: ContributionNeed };
+    result: void;
+  };
+  /** Entity admin (or higher) updates the entity details. Status and end date of an existing entity only change through the lifecycle methods. */
+  set_entity: {
+    args: { account_id: AccountId; entity: Entity };
+    result: void;
+  };
+  /** Replaces `old_moderator_id` with `moderator_id` in the council, or replaces the whole council with `moderator_id` if no old moderator is given. Needs `threshold` approvals. */
+  set_moderator: {
+    args: { moderator_id: AccountId; old_moderator_id?: AccountId | null };
+    result: void;
+  };
+  /** Sets the number of moderator approvals sensitive actions need. Without a threshold, a single moderator is enough. Needs approvals under the current threshold. */
+  set_moderator_threshold: {
+    args: { threshold?: number | null };
+    result: void;
+  };
+  /** Sets the time between staging an upgrade and being able to deploy it, in nanoseconds. Needs `threshold` moderator approvals. */
+  set_upgrade_delay: {
+    args: { delay: string };
+    result: void;
+  };
+  /** Moderator updates the validation limits. */
+  set_validation_config: {
+    args: { config: ValidationConfig };
+    result: void;
+  };
+  /** Deposit NEAR to cover the storage of the given account (predecessor by default). */
+  storage_deposit: {
+    args: { account_id?: AccountId | null; registration_only?: boolean | null };
+    result: StorageBalance;
+  };
+  /** Unregister and withdraw the whole storage deposit. Only possible once the account doesn't use any state. Force unregistration is not supported. */
+  storage_unregister: {
+    args: { force?: boolean | null };
+    result: boolean;
+  };
+  /** Withdraw available storage deposit (all of it by default). */
+  storage_withdraw: {
+    args: { amount?: string | null };
+    result: StorageBalance;
+  };
+  /** Contributor submits a pending or rejected milestone of their ongoing contribution for the entity to approve. */
+  submit_milestone: {
+    args: { entity_id: AccountId; contribution_id: number; milestone_id: number };
+    result: void;
+  };
+  /** Entity owner (or moderator) hands the ownership of the entity to another contributor. Previous owners stay on as admins. */
+  transfer_ownership: {
+    args: { entity_id: AccountId; owner_id: AccountId };
+    result: void;
+  };
+  /** Moderator removes the flag from the entity. The entity goes back to being closed if it has an end date, or active otherwise. */
+  unflag_entity: {
+    args: { account_id: AccountId; reason: string };
+    result: void;
+  };
+  /** Moderator unpauses the given categories of methods, or everything if none are given. */
+  unpause: {
+    args: { categories?: PauseCategory[] | null };
+    result: void;
+  };
+  /** Entity admin (or higher) verifies a contribution claimed by the contributor. */
+  verify_contribution: {
+    args: { entity_id: AccountId; contributor_id: AccountId; contribution_id: number };
+    result: void;
+  };
+  /** Sends the caller the whole amount of the token it can claim. Requires exactly 1 yoctoNEAR attached. */
+  withdraw: {
+    args: { token: Token };
+    result: unknown;
+  };
+  /** Contributor withdraws a contribution request they sent. */
+  withdraw_contribution_request: {
+    args: { request_id: string };
+    result: void;
+  };
+}
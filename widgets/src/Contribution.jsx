@@ -5,11 +5,8 @@ const accountId = context.accountId;
 const isEntity = props.isEntity ?? false;
 
 State.init({
-  finishFormHidden: true,
   contribution: null,
   contributionFetched: false,
-  isAuthorized: false,
-  isAuthorizedFetched: false,
   profile: null,
   profileFetched: false,
 });
@@ -30,18 +27,6 @@ if (!state.contributionFetched) {
   );
 }
 
-if (!state.isAuthorizedFetched) {
-  Near.asyncView(
-    ownerId,
-    "check_is_manager_or_higher",
-    { account_id: accountId, entity_id: entityId },
-    "final",
-    false
-  ).then((isAuthorized) =>
-    State.update({ isAuthorized, isAuthorizedFetched: true })
-  );
-}
-
 if (!state.profileFetched) {
   const profile = Social.getr(
     `${isEntity ? contributorId : entityId}/profile`,
@@ -93,22 +78,17 @@ return (
             <ActionColumn>
               <Widget
                 src={`${ownerId}/widget/ActiveIndicator`}
-                props={{ active: !state.contribution.end_date }}
+                props={{
+                  active: state.contribution.details.some(
+                    ({ end_date }) => !end_date
+                  ),
+                }}
               />
               <Widget
                 src={`${ownerId}/widget/CardMenu`}
                 props={{
                   update: props.update,
                   items: [
-                    state.isAuthorized
-                      ? {
-                        text: "Stop contribution",
-                        icon: "bi-slash-circle",
-                        id: "stop",
-                        onClick: () =>
-                          State.update({ finishFormHidden: false }),
-                      }
-                      : null,
                     {
                       text: "View details",
                       icon: "bi-info-circle",
@@ -127,17 +107,7 @@ return (
                       icon: "bi-arrow-up-right",
                       id: "share",
                     },
-                  ].filter((item) => item !== null),
-                }}
-              />
-              <Widget
-                src={`${ownerId}/widget/ContributionForm`}
-                props={{
-                  id: `${entityId}${contributorId}ContributionForm`,
-                  entityId: entityId,
-                  contributorId: contributorId,
-                  hidden: state.finishFormHidden,
-                  onClose: () => State.update({ finishFormHidden: true }),
+                  ],
                 }}
               />
             </ActionColumn>
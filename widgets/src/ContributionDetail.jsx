@@ -3,6 +3,12 @@ const entityId = props.entityId;
 const contributionType = props.contributionType;
 const description = props.description;
 const need = props.need;
+const contributorId = props.contributorId;
+const contributionId = props.contributionId;
+
+State.init({
+  finishFormHidden: true,
+});
 
 const formatDate = (date) => {
   if (date.length > 13) {
@@ -13,14 +19,51 @@ const formatDate = (date) => {
 };
 
 const startDate = formatDate(props.startDate);
-const endDate = formatDate(props.endDate);
+const endDate = props.endDate ? formatDate(props.endDate) : "now";
 
 return (
   <div className="border-bottom border-secondary-subtle">
     <div className="px-3 py-0">
-      <h4>
-        {contributionType} from {startDate} to {endDate}
-      </h4>
+      <div className="d-flex flex-row justify-content-between align-items-center">
+        <h4>
+          {contributionType} from {startDate} to {endDate}
+          {props.verification && props.verification !== "Verified" ? (
+            <span
+              className={`badge ms-2 ${
+                props.verification === "Disputed" ? "bg-danger" : "bg-secondary"
+              }`}
+            >
+              {props.verification}
+            </span>
+          ) : (
+            <></>
+          )}
+        </h4>
+        {props.isAuthorized && !props.endDate ? (
+          <>
+            <a
+              className="btn btn-danger text-light"
+              onClick={() => State.update({ finishFormHidden: false })}
+            >
+              <i className="bi-slash-circle" />
+              <span className="text-nowrap">Stop contribution</span>
+            </a>
+            <Widget
+              src={`${ownerId}/widget/ContributionForm`}
+              props={{
+                id: `${entityId}${contributorId}${contributionId}ContributionForm`,
+                entityId,
+                contributorId,
+                contributionId,
+                hidden: state.finishFormHidden,
+                onClose: () => State.update({ finishFormHidden: true }),
+              }}
+            />
+          </>
+        ) : (
+          <></>
+        )}
+      </div>
       {need ? (
         <a
           href={`/#/${ownerId}/widget/Index?accountId=${entityId}&cid=${cid}`}
@@ -1,6 +1,30 @@
 const ownerId = "contribut3.near";
+const storageDeposit = "100000000000000000000000";
+const storageBalance = Near.view(
+  ownerId,
+  "storage_balance_of",
+  { account_id: context.accountId },
+  "final",
+  true
+);
+
+// Writes are paid for from the storage deposit, so top it up first when it runs low.
+const callWithStorage = (methodName, args, gas, deposit) => {
+  const transactions = [];
+  if (!storageBalance || Big(storageBalance.available).lt(storageDeposit)) {
+    transactions.push({
+      contractName: ownerId,
+      methodName: "storage_deposit",
+      args: {},
+      deposit: storageDeposit,
+    });
+  }
+  transactions.push({ contractName: ownerId, methodName, args, gas, deposit });
+  Near.call(transactions);
+};
 const entityId = props.entityId;
 const contributorId = props.contributorId;
+const contributionId = props.contributionId;
 const id = props.id;
 const createDate = () => {
   const date = new Date();
@@ -19,10 +43,11 @@ const onSubmit = () => {
   const args = {
     entity_id: entityId,
     contributor_id: contributorId,
+    contribution_id: contributionId,
     end_date: `${new Date(state.endDate).getTime()}`,
   };
 
-  Near.call(ownerId, "finish_contribution", args);
+  callWithStorage("finish_contribution", args);
 };
 
 const body = (
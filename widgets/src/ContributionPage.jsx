@@ -14,10 +14,6 @@ if (!entityId || !contributorId) {
   return "Cannot show contribution without entity and contributor ID!";
 }
 
-State.init({
-  finishFormHidden: true,
-});
-
 const contribution = Near.view(
   ownerId,
   "get_contribution",
@@ -46,30 +42,7 @@ const contributions = Near.view(
   "final"
 );
 
-const controls = isAuthorized ? (
-  <div className="d-flex flex-column justify-content-start align-items-stretch">
-    <a
-      className="btn btn-danger me-2 text-light"
-      style={{ width: "13em" }}
-      onClick={() => State.update({ finishFormHidden: false })}
-    >
-      <i className="bi-slash-circle" />
-      <span className="text-nowrap">Stop contribution</span>
-    </a>
-    <Widget
-      src={`${ownerId}/widget/ContributionForm`}
-      props={{
-        id: `${entityId}${contributorId}ContributionForm`,
-        entityId: entityId,
-        contributorId: contributorId,
-        hidden: state.finishFormHidden,
-        onClose: () => State.update({ finishFormHidden: true }),
-      }}
-    />
-  </div>
-) : (
-  <></>
-);
+const isActive = contribution.details.some(({ end_date }) => !end_date);
 
 const body = (
   <div className="px-3">
@@ -82,30 +55,20 @@ const body = (
             isEntity: false,
             imageSize: "4em",
             update: props.update,
-            additionalColumn: controls,
             additionalRow: (
               <>
                 <div className="d-flex flex-row justify-content-start align-items-center">
                   <span className="text-muted me-2">
-                    From {formatDate(contribution.current.start_date)}
+                    From {formatDate(contribution.details[0].start_date)}
                   </span>
-                  {!!contribution.current.end_date ? (
-                    <span>To {formatDate(contribution.current.end_date)}</span>
-                  ) : (
-                    <></>
-                  )}
                   <Widget
                     src={`${ownerId}/widget/ActiveIndicator`}
-                    props={{ active: !contribution.current.end_date }}
+                    props={{ active: isActive }}
                   />
                 </div>
                 <div className="d-flex flex-row justify-content-start align-items-center">
                   <span className="me-2">
-                    contributed{" "}
-                    {typeof contribution.current.contribution_type === "string"
-                      ? contribution.current.contribution_type
-                      : contribution.current.contribution_type.Other}{" "}
-                    to
+                    {isActive ? "contributes" : "contributed"} to
                   </span>
                   <Widget
                     src={`${ownerId}/widget/ProfileLine`}
@@ -123,14 +86,13 @@ const body = (
         />
       </div>
     </div>
-    <Widget
-      src={`${ownerId}/widget/DescriptionArea`}
-      props={{ description: contribution.current.description }}
-    />
   </div>
 );
 
-const content = contribution.history
+// Newest contributions first.
+const content = contribution.details
+  .map((detail, contributionId) => ({ ...detail, contributionId }))
+  .reverse()
   .filter(
     ({ description, contribution_type }) =>
       description.includes(props.search) ||
@@ -140,12 +102,23 @@ const content = contribution.history
       ).includes(props.search)
   )
   .map(
-    ({ description, contribution_type, need, start_date, end_date }, index) => (
+    ({
+      description,
+      contribution_type,
+      need,
+      start_date,
+      end_date,
+      verification,
+      contributionId,
+    }) => (
       <Widget
         src={`${ownerId}/widget/ContributionDetail`}
         props={{
           need,
           entityId,
+          contributorId,
+          contributionId,
+          isAuthorized,
           description,
           contributionType:
             typeof contribution_type === "string"
@@ -153,8 +126,10 @@ const content = contribution.history
               : contribution_type.Other,
           startDate: start_date,
           endDate: end_date,
+          verification,
+          update: props.update,
         }}
-        key={index}
+        key={contributionId}
       />
     )
   );
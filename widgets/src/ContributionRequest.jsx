@@ -1,10 +1,33 @@
 const ownerId = "contribut3.near";
+const storageDeposit = "100000000000000000000000";
+const storageBalance = Near.view(
+  ownerId,
+  "storage_balance_of",
+  { account_id: context.accountId },
+  "final",
+  true
+);
+
+// Writes are paid for from the storage deposit, so top it up first when it runs low.
+const callWithStorage = (methodName, args, gas, deposit) => {
+  const transactions = [];
+  if (!storageBalance || Big(storageBalance.available).lt(storageDeposit)) {
+    transactions.push({
+      contractName: ownerId,
+      methodName: "storage_deposit",
+      args: {},
+      deposit: storageDeposit,
+    });
+  }
+  transactions.push({ contractName: ownerId, methodName, args, gas, deposit });
+  Near.call(transactions);
+};
 const accountId = context.accountId;
-const entityId = props.entityId;
-const contributorId = props.contributorId;
+const requestId = props.requestId;
+const search = props.search ?? "";
 
-if (!entityId || !contributorId) {
-  return "Cannot show contribution request without entityId or contributorId!";
+if (!requestId) {
+  return "Cannot show contribution request without requestId!";
 }
 
 State.init({
@@ -16,30 +39,30 @@ State.init({
   needFetched: false,
 });
 
-if (!state.isAuthorizedFetched) {
+if (!state.contributionRequestFetched) {
   Near.asyncView(
     ownerId,
-    "check_is_manager_or_higher",
-    { entity_id: entityId, account_id: context.accountId },
+    "get_contribution_request",
+    { request_id: requestId },
     "final",
     false
-  ).then((isAuthorized) =>
-    State.update({ isAuthorized, isAuthorizedFetched: true })
+  ).then((contributionRequest) =>
+    State.update({ contributionRequest, contributionRequestFetched: true })
   );
 }
 
-if (!state.contributionRequestFetched) {
+const entityId = state.contributionRequest?.entity_id;
+const contributorId = state.contributionRequest?.contributor_id;
+
+if (!state.isAuthorizedFetched && state.contributionRequestFetched && entityId) {
   Near.asyncView(
     ownerId,
-    "get_contribution_request",
-    {
-      entity_id: entityId,
-      contributor_id: contributorId,
-    },
+    "check_is_manager_or_higher",
+    { entity_id: entityId, account_id: context.accountId },
     "final",
     false
-  ).then((contributionRequest) =>
-    State.update({ contributionRequest, contributionRequestFetched: true })
+  ).then((isAuthorized) =>
+    State.update({ isAuthorized, isAuthorizedFetched: true })
   );
 }
 
@@ -61,6 +84,10 @@ if (!state.contributionRequest) {
   return "Loading...";
 }
 
+if (!entityId.includes(search) && !contributorId.includes(search)) {
+  return <></>;
+}
+
 const Controls = styled.div`
   flex-direction: column;
   justify-content: start;
@@ -116,10 +143,7 @@ const controls = (
   <Controls isAuthorized={state.isAuthorized}>
     <AcceptButton
       onClick={() =>
-        Near.call(ownerId, "approve_contribution", {
-          entity_id: entityId,
-          contributor_id: contributorId,
-        })
+        callWithStorage("approve_contribution", { request_id: requestId })
       }
     >
       <IconContainer>
@@ -144,10 +168,7 @@ const controls = (
     <RejectButton
       style={{ minWidth: "7em" }}
       onClick={() =>
-        Near.call(ownerId, "reject_contribution", {
-          entity_id: entityId,
-          contributor_id: contributorId,
-        })
+        Near.call(ownerId, "reject_contribution", { request_id: requestId })
       }
     >
       <IconContainer>
@@ -194,7 +215,7 @@ const DescriptionWrapper = styled.div`
 `;
 
 return (
-  <Container id={`${entityId}-${contributorId}`}>
+  <Container id={`request-${requestId}`}>
     <Wrapper>
       <Widget
         src={`${ownerId}/widget/ProfileLine`}
@@ -1,4 +1,27 @@
 const ownerId = "contribut3.near";
+const storageDeposit = "100000000000000000000000";
+const storageBalance = Near.view(
+  ownerId,
+  "storage_balance_of",
+  { account_id: context.accountId },
+  "final",
+  true
+);
+
+// Writes are paid for from the storage deposit, so top it up first when it runs low.
+const callWithStorage = (methodName, args, gas, deposit) => {
+  const transactions = [];
+  if (!storageBalance || Big(storageBalance.available).lt(storageDeposit)) {
+    transactions.push({
+      contractName: ownerId,
+      methodName: "storage_deposit",
+      args: {},
+      deposit: storageDeposit,
+    });
+  }
+  transactions.push({ contractName: ownerId, methodName, args, gas, deposit });
+  Near.call(transactions);
+};
 const id = props.id;
 
 const convertType = (contributionType) => {
@@ -175,7 +198,7 @@ const onConfirm = () => {
     need: state.need,
   };
 
-  Near.call(ownerId, "request_contribution", args);
+  callWithStorage("request_contribution", args);
 };
 
 const Page = styled.div`
@@ -22,27 +22,14 @@ if (state.items.length === 0) {
     { account_id: accountId ?? context.accountId, ...(cid ? { cid } : {}) },
     "final",
     false
-  ).then((requests) => {
-    if (accountId) {
-      State.update({
-        items: requests
-          .map((contributorId) => [accountId, contributorId])
-          .sort(),
-        shown: requests
-          .map((contributorId) => [accountId, contributorId])
-          .slice(0, limit),
-        from: limit,
-        hasMore: requests.length > limit,
-      });
-    } else {
-      State.update({
-        items: requests.sort(),
-        shown: requests.slice(0, limit),
-        from: limit,
-        hasMore: requests.length > limit,
-      });
-    }
-  });
+  ).then((requests) =>
+    State.update({
+      items: requests,
+      shown: requests.slice(0, limit),
+      from: limit,
+      hasMore: requests.length > limit,
+    })
+  );
 }
 
 const loadMore = () => {
@@ -59,22 +46,13 @@ const WidgetContainer = styled.div`
 
 return (
   <InfiniteScroll loadMore={loadMore} hasMore={state.hasMore}>
-    {state.shown
-      .filter(
-        ([entityId, contributorId]) =>
-          entityId.includes(search) || contributorId.includes(search)
-      )
-      .map(([entityId, contributorId]) => (
-        <WidgetContainer key={`${entityId}-${contributorId}`}>
-          <Widget
-            src={`${ownerId}/widget/ContributionRequest`}
-            props={{
-              entityId,
-              contributorId,
-              update: props.update,
-            }}
-          />
-        </WidgetContainer>
-      ))}
+    {state.shown.map((requestId) => (
+      <WidgetContainer key={requestId}>
+        <Widget
+          src={`${ownerId}/widget/ContributionRequest`}
+          props={{ requestId, search, update: props.update }}
+        />
+      </WidgetContainer>
+    ))}
   </InfiniteScroll>
 );
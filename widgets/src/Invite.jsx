@@ -1,4 +1,27 @@
 const ownerId = "contribut3.near";
+const storageDeposit = "100000000000000000000000";
+const storageBalance = Near.view(
+  ownerId,
+  "storage_balance_of",
+  { account_id: context.accountId },
+  "final",
+  true
+);
+
+// Writes are paid for from the storage deposit, so top it up first when it runs low.
+const callWithStorage = (methodName, args, gas, deposit) => {
+  const transactions = [];
+  if (!storageBalance || Big(storageBalance.available).lt(storageDeposit)) {
+    transactions.push({
+      contractName: ownerId,
+      methodName: "storage_deposit",
+      args: {},
+      deposit: storageDeposit,
+    });
+  }
+  transactions.push({ contractName: ownerId, methodName, args, gas, deposit });
+  Near.call(transactions);
+};
 const entityId = props.entityId;
 const contributorId = props.accountId ?? context.accountId;
 
@@ -69,7 +92,7 @@ const controls = (
   <Controls isAuthorized={contributorId === context.accountId}>
     <AcceptButton
       onClick={() =>
-        Near.call(ownerId, "accept_invite", { account_id: entityId })
+        callWithStorage("accept_invite", { account_id: entityId })
       }
     >
       <IconContainer>
@@ -8,6 +8,29 @@ const createDate = () => {
 };
 
 const ownerId = "contribut3.near";
+const storageDeposit = "100000000000000000000000";
+const storageBalance = Near.view(
+  ownerId,
+  "storage_balance_of",
+  { account_id: context.accountId },
+  "final",
+  true
+);
+
+// Writes are paid for from the storage deposit, so top it up first when it runs low.
+const callWithStorage = (methodName, args, gas, deposit) => {
+  const transactions = [];
+  if (!storageBalance || Big(storageBalance.available).lt(storageDeposit)) {
+    transactions.push({
+      contractName: ownerId,
+      methodName: "storage_deposit",
+      args: {},
+      deposit: storageDeposit,
+    });
+  }
+  transactions.push({ contractName: ownerId, methodName, args, gas, deposit });
+  Near.call(transactions);
+};
 const accountId = props.accountId ?? "";
 const kind = props.kind ? [{ name: props.kind }] : [];
 const startDate = props.startDate ?? createDate();
@@ -50,7 +73,7 @@ const onSubmit = () => {
     permissions: state.permissions.map(({ name }) => name),
   };
 
-  Near.call(ownerId, "invite_contributor", args);
+  callWithStorage("invite_contributor", args);
 };
 
 const header = <div className="card-header">Invite contributor</div>;
@@ -184,8 +207,8 @@ const body = (
             permissions,
           })
         }
-        options={[{ name: "Admin" }]}
-        placeholder="Admin or leave blank"
+        options={[{ name: "Admin" }, { name: "Manager" }, { name: "Member" }]}
+        placeholder="Admin, Manager, Member or leave blank"
         selected={state.permissions}
         positionFixed
       />
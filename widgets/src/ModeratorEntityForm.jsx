@@ -1,4 +1,27 @@
 const ownerId = "contribut3.near";
+const storageDeposit = "100000000000000000000000";
+const storageBalance = Near.view(
+  ownerId,
+  "storage_balance_of",
+  { account_id: context.accountId },
+  "final",
+  true
+);
+
+// Writes are paid for from the storage deposit, so top it up first when it runs low.
+const callWithStorage = (methodName, args, gas, deposit) => {
+  const transactions = [];
+  if (!storageBalance || Big(storageBalance.available).lt(storageDeposit)) {
+    transactions.push({
+      contractName: ownerId,
+      methodName: "storage_deposit",
+      args: {},
+      deposit: storageDeposit,
+    });
+  }
+  transactions.push({ contractName: ownerId, methodName, args, gas, deposit });
+  Near.call(transactions);
+};
 const accountId = props.accountId ? [{ name: props.accountId }] : [];
 
 const kind = props.kind ? [{ name: props.kind }] : [];
@@ -139,7 +162,7 @@ const onSubmit = () => {
     },
   };
 
-  Near.call(ownerId, "set_entity", args);
+  callWithStorage("set_entity", args);
 };
 
 const header = (
@@ -1,4 +1,27 @@
 const ownerId = "contribut3.near";
+const storageDeposit = "100000000000000000000000";
+const storageBalance = Near.view(
+  ownerId,
+  "storage_balance_of",
+  { account_id: context.accountId },
+  "final",
+  true
+);
+
+// Writes are paid for from the storage deposit, so top it up first when it runs low.
+const callWithStorage = (methodName, args, gas, deposit) => {
+  const transactions = [];
+  if (!storageBalance || Big(storageBalance.available).lt(storageDeposit)) {
+    transactions.push({
+      contractName: ownerId,
+      methodName: "storage_deposit",
+      args: {},
+      deposit: storageDeposit,
+    });
+  }
+  transactions.push({ contractName: ownerId, methodName, args, gas, deposit });
+  Near.call(transactions);
+};
 const accountId = props.accountId;
 const contributionType = props.contributionType
   ? [{ name: props.contributionType }]
@@ -126,7 +149,7 @@ const onSubmit = () => {
     contribution_type: convertType(state.contributionType[0]),
   };
 
-  Near.call(ownerId, "post_contribution_need", args);
+  callWithStorage("post_contribution_need", args);
 };
 
 const Page = styled.div`
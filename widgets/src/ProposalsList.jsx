@@ -1,6 +1,5 @@
 const ownerId = "contribut3.near";
 const search = props.search ?? "";
-const accountId = props.accountId;
 const limit = 10;
 
 State.init({
@@ -19,7 +18,7 @@ if (state.items.length === 0) {
     false
   ).then((requests) =>
     State.update({
-      items: requests.sort(),
+      items: requests,
       shown: requests.slice(0, limit),
       from: limit,
       hasMore: requests.length > limit,
@@ -41,19 +40,13 @@ const WidgetContainer = styled.div`
 
 return (
   <InfiniteScroll loadMore={loadMore} hasMore={state.hasMore}>
-    {state.shown
-      .filter((entityId) => entityId.includes(search))
-      .map((entityId) => (
-        <WidgetContainer key={entityId}>
-          <Widget
-            src={`${ownerId}/widget/ContributionRequest`}
-            props={{
-              entityId,
-              contributorId: accountId,
-              update: props.update,
-            }}
-          />
-        </WidgetContainer>
-      ))}
+    {state.shown.map((requestId) => (
+      <WidgetContainer key={requestId}>
+        <Widget
+          src={`${ownerId}/widget/ContributionRequest`}
+          props={{ requestId, search, update: props.update }}
+        />
+      </WidgetContainer>
+    ))}
   </InfiniteScroll>
 );